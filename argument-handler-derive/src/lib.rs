@@ -0,0 +1,252 @@
+// Copyright (c) 2023 Zoe <zoe@zyoh.ca>
+
+// `#[derive(Args)]` reads a plain struct and expands to the same `config_impl!` invocation
+// that `config_setup!` produces, so both share parsing, help and schema behavior.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, parse_macro_input, Attribute, Data, DeriveInput, Expr, ExprArray, Field, Fields, Ident, Lit, LitInt, LitStr, Token};
+
+#[proc_macro_derive(Args, attributes(args, arg))]
+pub fn derive_args(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+#[derive(Default)]
+struct Header {
+    executable: Option<LitStr>,
+    about: Option<LitStr>,
+    long_about: Option<LitStr>,
+    examples: Vec<(LitStr, LitStr)>,
+    after_help: Option<LitStr>,
+    duplicates: Option<Expr>,
+}
+
+enum Setting {
+    Flag(Ident),
+    Value(Ident, Expr),
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    keys: Vec<LitStr>,
+    position: Option<LitInt>,
+    default: Option<Expr>,
+    default_display: Option<LitStr>,
+    // The fields a derived default reads, and its value.
+    derived: Option<(Vec<Ident>, Expr)>,
+    internal: Option<Expr>,
+    settings: Vec<Setting>,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(&input.generics, "`Args` can't be derived for generic structs."));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(&input.ident, "`Args` needs a struct with named fields.")),
+        },
+        _ => return Err(syn::Error::new_spanned(&input.ident, "`Args` can only be derived for structs.")),
+    };
+
+    let header = parse_header(&input.attrs)?;
+    let executable = header.executable.ok_or_else(|| {
+        syn::Error::new_spanned(&input.ident, "Missing `#[args(executable = \"...\")]`.")
+    })?;
+
+    let mut arguments = Vec::new();
+    let mut derived = Vec::new();
+    let mut internals = Vec::new();
+    for field in fields {
+        let name = field.ident.as_ref().unwrap();
+        let mut options = parse_field(field)?;
+
+        if let Some((dependencies, value)) = options.derived.take() {
+            if options.internal.is_some() {
+                return Err(syn::Error::new_spanned(name, "Internal fields can't have a derived default."));
+            }
+            derived.push(quote! { #name(#(#dependencies),*) = #value; });
+        }
+        if let Some(value) = options.internal {
+            let cfgs = attrs_named(&field.attrs, "cfg");
+            internals.push(quote! { [#(#cfgs)*] #name = #value; });
+            continue;
+        }
+        arguments.push(expand_argument(field, name, options)?);
+    }
+
+    if arguments.is_empty() {
+        return Err(syn::Error::new_spanned(&input.ident, "`Args` needs at least one argument field."));
+    }
+
+    let struct_name = &input.ident;
+    let module = format_ident!("__argument_handler_{}", struct_name);
+
+    let about = header.about.map(|about| quote! { @about #about; });
+    let long_about = header.long_about.map(|long_about| quote! { @long_about #long_about; });
+    let examples = (!header.examples.is_empty()).then(|| {
+        let examples = header.examples.iter().map(|(command, explanation)| quote! { #command => #explanation; });
+        quote! { @examples { #(#examples)* } }
+    });
+    let after_help = header.after_help.map(|after_help| quote! { @after_help #after_help; });
+    let duplicates = header.duplicates.map(|duplicates| quote! { @duplicates #duplicates; });
+    let derived = (!derived.is_empty()).then(|| quote! { @derived { #(#derived)* } });
+    let internals = (!internals.is_empty()).then(|| quote! { @internal { #(#internals)* } });
+
+    Ok(quote! {
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        mod #module {
+            use std::error::Error;
+
+            // Field types come from the module of the struct.
+            #[allow(unused_imports)]
+            use super::*;
+
+            #[allow(unused_imports)]
+            use ::argument_handler::{
+                Argument, ArgumentOrder, CLIValue, DuplicatePolicy, Example, HelpDetail, HelpOptions, HelpText,
+                Optional, ParseError, ParseOptions, ParseStyle, ParseWarning, SplitAt, ValueError
+            };
+            #[allow(unused_imports)]
+            use ::argument_handler::{
+                DefaultParser, DefaultValue, DisplayedDefault, FlagValue, OptionalValue, ParseOptionValue, ParseOsValue,
+                ParseStrValue, RequiredValue, TextDefault, TypedDefault, ValueParser, ValueTaking
+            };
+
+            ::argument_handler::config_impl! {
+                struct #struct_name for #executable;
+
+                #about
+                #long_about
+                #examples
+                #after_help
+                #duplicates
+
+                #(#arguments)*
+
+                #derived
+                #internals
+            }
+        }
+    })
+}
+
+fn parse_header(attrs: &[Attribute]) -> syn::Result<Header> {
+    let mut header = Header::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("args")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("executable") {
+                header.executable = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("about") {
+                header.about = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("long_about") {
+                header.long_about = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("after_help") {
+                header.after_help = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("duplicates") {
+                header.duplicates = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("example") {
+                // example("<arguments>", "<explanation>")
+                let content;
+                parenthesized!(content in meta.input);
+                let command: LitStr = content.parse()?;
+                content.parse::<Token![,]>()?;
+                let explanation: LitStr = content.parse()?;
+                header.examples.push((command, explanation));
+            } else {
+                return Err(meta.error("Unknown `args` setting."));
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(header)
+}
+
+fn parse_field(field: &Field) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+
+    for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("arg")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("keys") {
+                let keys: ExprArray = meta.value()?.parse()?;
+                for key in keys.elems {
+                    match key {
+                        Expr::Lit(syn::ExprLit { lit: Lit::Str(key), .. }) => options.keys.push(key),
+                        key => return Err(syn::Error::new_spanned(key, "Keys must be string literals.")),
+                    }
+                }
+            } else if meta.path.is_ident("position") {
+                options.position = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("default") {
+                options.default = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("default_display") {
+                options.default_display = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("derived") {
+                // derived(<field>, ...) = <value>
+                let content;
+                parenthesized!(content in meta.input);
+                let dependencies = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+                options.derived = Some((dependencies.into_iter().collect(), meta.value()?.parse()?));
+            } else if meta.path.is_ident("internal") {
+                options.internal = Some(meta.value()?.parse()?);
+            } else {
+                // Everything else is an argument setting, checked by `argument_setting!`.
+                let name = meta.path.get_ident().cloned().ok_or_else(|| meta.error("Expected a setting name."))?;
+                if meta.input.peek(Token![=]) {
+                    options.settings.push(Setting::Value(name, meta.value()?.parse()?));
+                } else {
+                    options.settings.push(Setting::Flag(name));
+                }
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(options)
+}
+
+// The field's `cfg` attributes, so a field left out is no argument either, and its doc comment,
+// for `config_impl!` to split into description and long help.
+fn attrs_named<'a>(attrs: &'a [Attribute], name: &str) -> Vec<&'a Attribute> {
+    attrs.iter()
+        .filter(|attr| attr.path().is_ident(name))
+        .collect()
+}
+
+fn expand_argument(field: &Field, name: &Ident, options: FieldOptions) -> syn::Result<TokenStream2> {
+    if options.keys.is_empty() {
+        return Err(syn::Error::new_spanned(name, "Missing `#[arg(keys = [...])]`."));
+    }
+
+    let cast = &field.ty;
+    let keys = &options.keys;
+    if options.default.is_none() && options.default_display.is_some() {
+        return Err(syn::Error::new_spanned(name, "`default_display` needs a `default`."));
+    }
+    let default_display = options.default_display.map(|display| quote! { => #display });
+    let default = options.default.map(|default| quote! { = #default #default_display });
+    let position = options.position.map(|position| quote! { ; #position });
+    let cfgs = attrs_named(&field.attrs, "cfg");
+    let docs = attrs_named(&field.attrs, "doc");
+    let settings = (!options.settings.is_empty()).then(|| {
+        let settings = options.settings.iter().map(|setting| match setting {
+            Setting::Flag(name) => quote! { #name },
+            Setting::Value(name, value) => quote! { #name = #value },
+        });
+        quote! { { #(#settings),* } }
+    });
+
+    Ok(quote! {
+        [#(#cfgs)*] #(#docs)*
+        #name: #cast #default, [#(#keys),* #position] #settings;
+    })
+}
@@ -0,0 +1,204 @@
+// Copyright (c) 2023 Zoe <zoe@zyoh.ca>
+
+use std::str::FromStr;
+
+use crate::{ParseStyle, DuplicatePolicy};
+
+pub enum CLIValue {
+    String(String),
+    Bool(bool),
+    None
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Optional<T> {
+    Some(T),
+    #[default]
+    None
+}
+
+impl<T> Optional<T> {
+    // Takes the value, so it neither needs `T: Clone` nor copies it.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_option(self) -> Option<T> {
+        self.into()
+    }
+
+    pub fn is_some(&self) -> bool {
+        matches!(self, Optional::Some(_))
+    }
+
+    pub fn is_none(&self) -> bool {
+        !self.is_some()
+    }
+
+    pub fn as_ref(&self) -> Optional<&T> {
+        match self {
+            Optional::Some(value) => Optional::Some(value),
+            Optional::None => Optional::None
+        }
+    }
+
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Optional<U> {
+        match self {
+            Optional::Some(value) => Optional::Some(f(value)),
+            Optional::None => Optional::None
+        }
+    }
+
+    pub fn unwrap_or(self, default: T) -> T {
+        self.to_option().unwrap_or(default)
+    }
+
+    pub fn iter(&self) -> std::option::IntoIter<&T> {
+        self.as_ref().into_iter()
+    }
+}
+
+impl<T> From<Option<T>> for Optional<T> {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => Optional::Some(value),
+            None => Optional::None
+        }
+    }
+}
+
+impl<T> From<Optional<T>> for Option<T> {
+    fn from(value: Optional<T>) -> Self {
+        match value {
+            Optional::Some(value) => Some(value),
+            Optional::None => None
+        }
+    }
+}
+
+impl<T> IntoIterator for Optional<T> {
+    type Item = T;
+    type IntoIter = std::option::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.to_option().into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Optional<T> {
+    type Item = &'a T;
+    type IntoIter = std::option::IntoIter<&'a T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: std::fmt::Display> std::fmt::Display for Optional<T> {
+    // An absent value displays as nothing.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Optional::Some(value) => value.fmt(f),
+            Optional::None => Ok(())
+        }
+    }
+}
+
+impl<T: FromStr> FromStr for Optional<T> {
+    // The inner type's error, so the reason a value was rejected is kept.
+    type Err = T::Err;
+
+    // Any text is a value, see the `none_value` setting for spelling an absent one.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        T::from_str(s).map(Optional::Some)
+    }
+}
+
+#[derive(Debug)]
+pub struct Argument<T> {
+    pub index: usize,
+    pub field_name: String,
+    pub value_type: String,
+    pub keys: Vec<String>,
+    pub aliases: Vec<String>,
+    pub hidden_aliases: Vec<String>,
+    pub description: String,
+    pub long_description: String,
+    pub hide_short_help: bool,
+    pub hidden: bool,
+    pub deprecated: Option<String>,
+    pub missing_value: Option<String>,
+    pub none_value: Option<String>,
+    // How the help shows the default, if it has one that can be shown.
+    pub default_display: Option<String>,
+    pub allow_hyphen_values: bool,
+    pub duplicates: Option<DuplicatePolicy>,
+    pub optional: bool,
+    pub position: Option<usize>,
+    pub section: Option<String>,
+    pub value: T
+}
+
+impl<T> Argument<T> {
+    pub fn matches_key(&self, key: &str) -> bool {
+        self.all_keys().any(|k| k == key)
+    }
+
+    pub fn matches_key_with(&self, key: &str, style: &ParseStyle) -> bool {
+        self.all_keys().any(|k| style.keys_match(k, key))
+    }
+
+    pub fn all_keys(&self) -> impl Iterator<Item = &String> {
+        self.keys.iter()
+            .chain(self.aliases.iter())
+            .chain(self.hidden_aliases.iter())
+    }
+
+    pub fn matches(&self, other: &Self) -> bool {
+        self.keys == other.keys
+    }
+
+    pub fn pretty_name(&self) -> String {
+        // Hidden aliases are accepted but never documented.
+        self.keys.iter()
+            .chain(self.aliases.iter())
+            .map(|k| k.as_str())
+            .collect::<Vec<&str>>()
+            .join(", ")
+    }
+}
+
+// `stringify!` spaces types from `#[derive(Args)]` token by token, e.g. `Option < u16 >`.
+// Joins them back the way `config_setup!` types read, `Option<u16>`.
+#[doc(hidden)]
+pub fn type_name(stringified: &str) -> String {
+    let mut name = String::new();
+    for piece in stringified.split_whitespace() {
+        let joined = name.is_empty()
+            || name.ends_with(['<', '&', ':', '(', '['])
+            || piece.starts_with(['<', '>', ':', ',', ';', '(', ')', '[', ']']);
+        if !joined {
+            name.push(' ');
+        }
+        name.push_str(piece);
+    }
+    name
+}
+
+// Splits the lines of a doc comment into the description, its first line, and the long help,
+// the remaining paragraphs with their lines joined up for the help message to wrap.
+#[doc(hidden)]
+pub fn doc_description(lines: &[&str]) -> (String, String) {
+    let mut lines = lines.iter().map(|line| line.trim()).skip_while(|line| line.is_empty());
+    let description = lines.next().unwrap_or_default().to_string();
+
+    // Paragraphs end at blank lines and are kept apart by one in the long help.
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in lines.chain([""]) {
+        if !line.is_empty() {
+            paragraph.push(line);
+        } else if !paragraph.is_empty() {
+            paragraphs.push(paragraph.join(" "));
+            paragraph.clear();
+        }
+    }
+    (description, paragraphs.join("\n\n"))
+}
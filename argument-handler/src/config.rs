@@ -0,0 +1,54 @@
+// Copyright (c) 2023 Zoe <zoe@zyoh.ca>
+
+use std::error::Error;
+use std::ffi::OsString;
+
+use crate::{Argument, ArgumentOrder, HelpDetail, HelpOptions, HelpText, ParseOptions, ParseWarning};
+
+// Implemented by every struct generated with `config_setup!`, so startup code can be written once for all configs.
+pub trait ArgumentConfig: Sized {
+    // The generated `ArgumentType`, one variant per field.
+    type Value;
+
+    const EXECUTABLE_NAME: &'static str;
+
+    fn arguments(order: ArgumentOrder) -> Result<Vec<Argument<Self::Value>>, Box<dyn Error>>;
+    fn sections() -> Vec<String>;
+    fn help_text() -> HelpText;
+    fn usage(options: Option<HelpOptions>) -> Result<String, Box<dyn Error>>;
+    fn help(options: Option<HelpOptions>) -> Result<String, Box<dyn Error>>;
+    fn requested_help(args: &[String], field_name: &str) -> Result<Option<HelpDetail>, Box<dyn Error>>;
+    fn render_error(error: &(dyn Error + 'static), args: &[String]) -> String;
+    fn parse_custom_os_with(args: Vec<OsString>, options: Option<ParseOptions>) -> Result<(Self, Vec<ParseWarning>), Box<dyn Error>>;
+
+    fn show_help(options: Option<HelpOptions>) -> Result<(), Box<dyn Error>> {
+        let help_message = Self::help(options)?;
+        println!("{}", help_message);
+        Ok(())
+    }
+
+    fn parse_custom_with(args: Vec<String>, options: Option<ParseOptions>) -> Result<(Self, Vec<ParseWarning>), Box<dyn Error>> {
+        Self::parse_custom_os_with(args.into_iter().map(OsString::from).collect(), options)
+    }
+
+    // Warnings are dropped here, the `_with` and `_with_warnings` variants return them.
+    fn parse_custom_os(args: Vec<OsString>) -> Result<Self, Box<dyn Error>> {
+        Self::parse_custom_os_with(args, None).map(|(result, _)| result)
+    }
+
+    fn parse_custom(args: Vec<String>) -> Result<Self, Box<dyn Error>> {
+        Self::parse_custom_os(args.into_iter().map(OsString::from).collect())
+    }
+
+    fn parse_os() -> Result<Self, Box<dyn Error>> {
+        Self::parse_custom_os(std::env::args_os().collect())
+    }
+
+    fn parse() -> Result<Self, Box<dyn Error>> {
+        Self::parse_os()
+    }
+
+    fn parse_with_warnings() -> Result<(Self, Vec<ParseWarning>), Box<dyn Error>> {
+        Self::parse_custom_os_with(std::env::args_os().collect(), None)
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright (c) 2023 Zoe <zoe@zyoh.ca>
+
+use std::cmp::Ordering;
+
+use crate::Argument;
+
+pub enum SplitAt {
+    Any,
+    Space,
+    None,
+    Custom(Vec<char>),
+}
+
+impl SplitAt {
+    #[allow(dead_code)]
+    pub fn matches(&self, character: char) -> bool {
+        match self {
+            SplitAt::Any => true,
+            SplitAt::Space => character == ' ',
+            SplitAt::None => false,
+            SplitAt::Custom(chars) => {
+                for c in chars {
+                    if *c == character {
+                        return true;
+                    }
+                }
+                false
+            }
+        }
+    }
+}
+
+pub enum ArgumentOrder {
+    Declaration,
+    Alphabetical,
+    RequiredFirst,
+}
+
+impl ArgumentOrder {
+    pub fn compare<T>(&self, a: &Argument<T>, b: &Argument<T>) -> Ordering {
+        match self {
+            ArgumentOrder::Declaration => a.index.cmp(&b.index),
+            ArgumentOrder::Alphabetical => {
+                let name = |argument: &Argument<T>| argument.pretty_name()
+                    .trim_start_matches('-')
+                    .to_lowercase();
+                name(a).cmp(&name(b))
+                    .then(a.index.cmp(&b.index))
+            },
+            ArgumentOrder::RequiredFirst => {
+                // Required before optional, then positionals by position, then declaration order.
+                a.optional.cmp(&b.optional)
+                    .then(a.position.unwrap_or(usize::MAX).cmp(&b.position.unwrap_or(usize::MAX)))
+                    .then(a.index.cmp(&b.index))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelpDetail {
+    Short,
+    Long,
+}
+
+impl HelpDetail {
+    pub fn for_key(key: &str) -> HelpDetail {
+        // `-h` asks for the compact form, `--help` for the full one.
+        if key.starts_with("--") {
+            HelpDetail::Long
+        } else {
+            HelpDetail::Short
+        }
+    }
+}
+
+pub struct HelpOptions {
+    pub description_offset: usize,
+    pub description_max_length: usize,
+    pub split_at: SplitAt,
+    pub description_newline_extra_padding: usize,
+    pub indent_length: usize,
+    pub separate_positionals: bool,
+    pub positionals_heading: String,
+    pub options_heading: String,
+    pub order: ArgumentOrder,
+    pub detail: HelpDetail
+}
+
+impl Default for HelpOptions {
+    fn default() -> Self {
+        Self {
+            description_offset: 8,
+            description_max_length: 50,
+            split_at: SplitAt::Space,
+            description_newline_extra_padding: 2,
+            indent_length: 4,
+            separate_positionals: true,
+            positionals_heading: String::from("Arguments"),
+            options_heading: String::from("Options"),
+            order: ArgumentOrder::Declaration,
+            detail: HelpDetail::Long
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Example {
+    pub command: String,
+    pub explanation: String
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HelpText {
+    pub about: Option<String>,
+    pub long_about: Option<String>,
+    pub examples: Vec<Example>,
+    pub after_help: Option<String>
+}
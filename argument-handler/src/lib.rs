@@ -4,9 +4,74 @@
 #![allow(unused_variables)]
 #![allow(dead_code)]
 
+mod argument;
+mod config;
+mod help;
+mod parse;
 mod tests;
-
-
+mod validate;
+
+pub use argument::*;
+pub use config::*;
+pub use help::*;
+pub use parse::*;
+pub use validate::*;
+
+#[cfg(feature = "derive")]
+pub use argument_handler_derive::Args;
+
+// Lets `#[derive(Args)]` output, which names `::argument_handler`, work inside this crate too.
+extern crate self as argument_handler;
+
+
+/// Declares a config struct with its arguments, see the README for the grammar.
+///
+/// Mistakes in the declaration don't compile, like a key taken twice:
+///
+/// ```compile_fail,E0080
+/// argument_handler::config_setup! {
+///     in config;
+///     #[derive(Debug)]
+///     pub struct Config for "app.exe";
+///     verbose: bool, ["-v", "--verbose"];
+///     version: bool, ["-v", "--version"];
+/// }
+/// ```
+///
+/// a gap before a position:
+///
+/// ```compile_fail,E0080
+/// argument_handler::config_setup! {
+///     in config;
+///     #[derive(Debug)]
+///     pub struct Config for "app.exe";
+///     input: String, ["input"; 1];
+/// }
+/// ```
+///
+/// a positional argument named like an option:
+///
+/// ```compile_fail,E0080
+/// argument_handler::config_setup! {
+///     in config;
+///     #[derive(Debug)]
+///     pub struct Config for "app.exe";
+///     input: String, ["-i"; 0];
+/// }
+/// ```
+///
+/// or a positional argument with more than one name:
+///
+/// ```compile_fail
+/// argument_handler::config_setup! {
+///     in config;
+///     #[derive(Debug)]
+///     pub struct Config for "app.exe";
+///     input: String, ["input", "source"; 0];
+/// }
+/// ```
+///
+/// Aliases are set at runtime, so names they share with other arguments are reported by `get_arguments`.
 #[macro_export]
 macro_rules! config_setup {
     (
@@ -15,19 +80,37 @@ macro_rules! config_setup {
         $(#[$attr:meta])*
         $public_flag:vis struct $struct_name:ident for $executable_name:literal;
 
-        // TODO: Don't allow `-` or `--` prefix when position is specified
-        // TODO: Don't allow multiple argument names when position is specified
+        $(@about $about:literal;)?
+        $(@long_about $long_about:literal;)?
         $(
-            $name:ident: $cast:ty $(= $default:expr)?, 
+            @examples {
+                $( $example_command:literal => $example_explanation:literal; )*
+            }
+        )?
+        $(@after_help $after_help:literal;)?
+        $(@duplicates $duplicates:expr;)?
+
+        $(
+            $(@section $section:literal;)?
+            $(#[$($field_attr:tt)*])*
+            // Either just the name, or `pub` with an optional restriction before it.
+            $field_lead:ident $(($($field_restrict:tt)+))? $($field_name:ident)?: $cast:ty $(= $default:expr $(=> $default_display:literal)?)?,
                 [$($cli_name:literal),+ $(; $cli_position:literal)?]
-                $(, $description:literal)?;
+                $(, $description:literal)?
+                $({ $( $setting:ident $(= $setting_value:expr)? ),* $(,)? })?;
         )+
 
+        $(
+            @derived {
+                $( $derived_name:ident($($derived_dependency:ident),*) = $derived_value:expr; )*
+            }
+        )?
+
         $(
             @internal {
                 $(
-                    $(#[$internal_attr:meta])*
-                    $internal_name:ident: $internal_cast:ty = $internal_value:expr;
+                    $(#[$($internal_attr:tt)*])*
+                    $internal_vis:vis $internal_name:ident: $internal_cast:ty = $internal_value:expr;
                 )*
             }
         )?
@@ -41,419 +124,1109 @@ macro_rules! config_setup {
         $public_flag mod $module {
 
             use std::error::Error;
-            use std::str::FromStr;
-            use std::collections::HashMap;
-
-            pub enum SplitAt {
-                Any,
-                Space,
-                None,
-                Custom(Vec<char>),
-            }
-
-            impl SplitAt {
-                #[allow(dead_code)]
-                pub fn matches(&self, character: char) -> bool {
-                    match self {
-                        SplitAt::Any => true,
-                        SplitAt::Space => character == ' ',
-                        SplitAt::None => false,
-                        SplitAt::Custom(chars) => {
-                            for c in chars {
-                                if *c == character {
-                                    return true;
-                                }
-                            }
-                            false
-                        }
-                    }
-                }
-            }
 
-            pub struct HelpOptions {
-                pub description_offset: usize,
-                pub description_max_length: usize,
-                pub split_at: SplitAt,
-                pub description_newline_extra_padding: usize,
-                pub indent_length: usize
-            }
+            // Field types come from the invoking module.
+            #[allow(unused_imports)]
+            use super::*;
 
-            impl Default for HelpOptions {
-                fn default() -> Self {
-                    Self {
-                        description_offset: 8,
-                        description_max_length: 50,
-                        split_at: SplitAt::Space,
-                        description_newline_extra_padding: 2,
-                        indent_length: 4
-                    }
+            // Shared by every config, so values and options of different configs are interchangeable.
+            #[allow(unused_imports)]
+            pub use $crate::{
+                Argument, ArgumentOrder, CLIValue, DuplicatePolicy, Example, HelpDetail, HelpOptions, HelpText,
+                Optional, ParseError, ParseOptions, ParseStyle, ParseWarning, SplitAt, ValueError
+            };
+            // Which of these traits is used depends on the field types.
+            #[allow(unused_imports)]
+            use $crate::{
+                DefaultParser, DefaultValue, DisplayedDefault, FlagValue, OptionalValue, ParseOptionValue, ParseOsValue,
+                ParseStrValue, RequiredValue, TextDefault, TypedDefault, ValueParser, ValueTaking
+            };
+
+            $crate::config_struct! {
+                $(#[$attr])*
+                $public_flag struct $struct_name {
+                    $(
+                        [$(#[$($field_attr)*])*] [$($field_name)? $field_lead $(($($field_restrict)+))?]: $cast,
+                    )*
                 }
-            }
-
-            pub enum CLIValue {
-                String(String),
-                Bool(bool),
-                None
-            }
-
-            #[derive(Debug, Copy, Clone)]
-            pub enum Optional<T> where T: FromStr + Default {
-                Some(T),
-                None
-            }
-
-            impl<T> Optional<T> where T: FromStr + Default + Clone {
-                pub fn to_option(&self) -> Option<T> {
-                    match self {
-                        Optional::Some(value) => Some(value.clone()),
-                        Optional::None => None
-                    }
+                {
+                    $(
+                        $(
+                            $(#[$($internal_attr)*])*
+                            $internal_vis $internal_name: $internal_cast,
+                        )*
+                    )?
                 }
             }
 
-            impl<T> FromStr for Optional<T> where T: FromStr + Default {
-                type Err = Box<dyn Error>;
+            $crate::config_cfg! {
+                {
+                    struct $struct_name for $executable_name;
 
-                fn from_str(s: &str) -> Result<Self, Self::Err> {
-                    let object: Result<Optional<T>, Self::Err> = match T::from_str(s) {
-                        Ok(value) => Ok(Optional::Some(value)),
-                        Err(_) => {
-                            if s == "None" {
-                                Ok(Optional::None)
-                            } else {
-                                Err("Invalid value for Optional.".into())
-                            }
-                        }
-                    };
-                    object
+                    $(@about $about;)?
+                    $(@long_about $long_about;)?
+                    $(@examples { $( $example_command => $example_explanation; )* })?
+                    $(@after_help $after_help;)?
+                    $(@duplicates $duplicates;)?
                 }
+                { $(@derived { $( $derived_name($($derived_dependency),*) = $derived_value; )* })? }
+                { $(@impl { $( $then_execute )* })? }
+                []
+                $(
+                    {field [] [] [$(#[$($field_attr)*])*] ($(@section $section;)?)
+                        $($field_name)? $field_lead: $cast $(= $default $(=> $default_display)?)?, [$($cli_name),+ $(; $cli_position)?] $(, $description)?
+                            $({ $( $setting $(= $setting_value)? ),* })?;}
+                )+
+                $($(
+                    {internal [] [] [$(#[$($internal_attr)*])*] $internal_name = $internal_value;}
+                )*)?
             }
 
-            impl<T> Default for Optional<T> where T: FromStr + Default {
-                fn default() -> Self {
-                    // let object: Optional<T> = Optional::Some(T::default());
-                    let object = Self::None;
-                    object
+            impl std::fmt::Display for $struct_name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "{:?}", self)
                 }
             }
+        }
+    }
+}
 
-            #[derive(Debug)]
-            pub struct Argument<T> {
-                pub field_name: String,
-                pub keys: Vec<String>,
-                pub description: String,
-                pub optional: bool,
-                pub position: Option<usize>,
-                pub value: T
-            }
+// The generated impls of a config struct, shared by `config_setup!` and `#[derive(Args)]`.
+// Expects the crate's types and the field types to be in scope.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! config_impl {
+    (
+        struct $struct_name:ident for $executable_name:literal;
 
-            impl<T> Argument<T> {
-                pub fn matches_key(&self, key: &str) -> bool {
-                    self.keys.iter().any(|k| k == key)
-                }
+        $(@about $about:literal;)?
+        $(@long_about $long_about:literal;)?
+        $(@examples { $( $example_command:literal => $example_explanation:literal; )* })?
+        $(@after_help $after_help:literal;)?
+        $(@duplicates $duplicates:expr;)?
 
-                pub fn matches(&self, other: &Self) -> bool {
-                    self.keys == other.keys
-                }
+        $(
+            // The `cfg` attributes of a field, which go on everything generated for it, then its doc comment.
+            [$(#[cfg($cfg:meta)])*]
+            $(@section $section:literal;)?
+            $(#[$($field_attr:tt)*])*
+            $name:ident $(pub)?: $cast:ty $(= $default:expr $(=> $default_display:literal)?)?, [$($cli_name:literal),+ $(; $cli_position:literal)?] $(, $description:literal)?
+                $({ $( $setting:ident $(= $setting_value:expr)? ),* })?;
+        )+
 
-                pub fn pretty_name(&self) -> String {
-                    self.keys.join(", ")
-                }
-            }
-            use super::*;
+        $(@derived { $( $derived_name:ident($($derived_dependency:ident),*) = $derived_value:expr; )* })?
 
-            $(#[$attr])*
-            $public_flag struct $struct_name {
-                $(
-                    pub $name: $cast,
-                )*
+        $(@internal { $( [$(#[cfg($internal_cfg:meta)])*] $internal_name:ident = $internal_value:expr; )* })?
 
-                $(
-                    $(
-                        $internal_name: $internal_cast,
-                    )*
-                )?
-            }
+        $(@impl { $( $then_execute:tt )* })?
+    ) => {
+        // Mistakes in the declaration fail to compile, fields left out by `cfg` don't count.
+        const ALL_KEYS: &[&[&str]] = &[$( $(#[cfg($cfg)])* &[$($cli_name),+], )*];
+        const ALL_POSITIONS: &[&[usize]] = &[$( $(#[cfg($cfg)])* &[$($cli_position)?], )*];
+        $(
+            $(#[cfg($cfg)])*
+            const _: () = assert!(
+                !$crate::has_duplicate_key(ALL_KEYS, &[$($cli_name),+]),
+                concat!("Argument `", stringify!($name), "` uses a key that is already taken.")
+            );
+            $(#[cfg($cfg)])*
+            $crate::check_position!($name [$($cli_name),+] $(; $cli_position)?);
+        )+
 
-            #[derive(Debug, Clone)]
-            pub enum ArgumentType {
-                $(
-                    #[allow(non_camel_case_types)]
-                    $name($cast),
-                )*
+        // Derived defaults with the fields they read.
+        const DERIVED: &[(&str, &[&str])] = &[$($( (stringify!($derived_name), &[$(stringify!($derived_dependency)),*]), )*)?];
+        $($(
+            const _: () = assert!(
+                $crate::count_derived(DERIVED, stringify!($derived_name)) == 1,
+                concat!("Argument `", stringify!($derived_name), "` has more than one derived default.")
+            );
+            const _: () = assert!(
+                !$crate::derived_cycle(DERIVED, stringify!($derived_name)),
+                concat!("The derived default of `", stringify!($derived_name), "` depends on itself.")
+            );
+        )*)?
+
+        #[allow(dead_code)]
+        #[derive(Debug, Clone)]
+        pub enum ArgumentType {
+            $(
+                $(#[cfg($cfg)])*
+                #[allow(non_camel_case_types)]
+                $name($cast),
+            )*
+
+            None
+        }
 
-                None
-            }
+        impl $struct_name {
+            pub(crate) fn get_arguments() -> Result<
+                                                Vec<Argument<ArgumentType>>, 
+                                                Box<dyn Error>
+                                            > {
+                let mut result: Vec<Argument<ArgumentType>> = Vec::new();
 
-            impl $struct_name {
-                pub(crate) fn get_arguments() -> Result<
-                                                    HashMap<String, Argument<ArgumentType>>, 
-                                                    Box<dyn Error>
-                                                > {
-                    let mut result: HashMap<String, Argument<ArgumentType>> = HashMap::new();
+                // Arguments belong to the last section declared before them.
+                #[allow(unused_mut)]
+                let mut section: Option<String> = None;
 
-                    $(                
-                        let mut cli_names: Vec<String> = Vec::new();
-                        $( cli_names.push($cli_name.to_string()); )*
+                $(
+                    // Fields left out by `cfg` aren't arguments either.
+                    $(#[cfg($cfg)])*
+                    {
+                        let cli_names: Vec<String> = vec![$( $cli_name.to_string() ),*];
 
-                        #[allow(unused_mut)]
-                        let mut description = String::new();
-                        $( description.push_str($description); )*
+                        $( section = Some($section.to_string()); )?
+
+                        // The first doc comment line is the description, the rest is the long help.
+                        #[allow(unused_mut, unused_assignments)]
+                        let (mut description, long_description) = $crate::doc_description(&$crate::doc_lines!($(#[$($field_attr)*])*));
+                        $( description = $description.to_string(); )*
 
                         let position: Option<usize> = None;
                         $( let position = Some($cli_position); )*
 
                         let field_name = stringify!($name).to_string();
 
-                        // Default value
-                        let default: Option<String> = None;
-                        $( let default = Some($default.to_string()); )*
+                        let parser = &&&ValueParser::<$cast>(std::marker::PhantomData);
 
-                        let actual_default = match &default {
-                            Some(default) => ArgumentType::$name(<$cast>::from_str(&default)?),
-                            None => ArgumentType::None
-                        };
+                        // Default value, either of the field type or text parsed like a command line value
+                        #[allow(unused_mut, unused_assignments)]
+                        let mut default_display: Option<String> = None;
+                        let actual_default = ArgumentType::None;
+                        $(
+                            // An explicit display string replaces the one taken from the default.
+                            #[allow(unused_assignments)]
+                            let actual_default = match (&&&DefaultParser::<$cast, _>(std::marker::PhantomData, $default)).default_value() {
+                                DefaultValue::Value(value, shown) => {
+                                    default_display = shown;
+                                    ArgumentType::$name(value)
+                                },
+                                DefaultValue::Text(text) => {
+                                    let value = parser.parse_value(std::ffi::OsStr::new(&text), &ParseStyle::default())
+                                        .map_err(|error| format!("Invalid default `{}` for argument `{}`: {}", text, field_name, error))?;
+                                    default_display = Some(text);
+                                    ArgumentType::$name(value)
+                                }
+                            };
+                            $( default_display = Some($default_display.to_string()); )?
+                        )?
 
-                        let optional = stringify!($cast).starts_with("Optional") || default.is_some();
+                        let derived = DERIVED.iter().any(|(name, _)| *name == field_name);
+                        // Flags are false when left out, so they are never required.
+                        let optional = parser.is_optional() || parser.is_flag() || derived || !matches!(actual_default, ArgumentType::None);
 
-                        let argument = Argument::<ArgumentType> {
+                        #[allow(unused_mut)]
+                        let mut argument = Argument::<ArgumentType> {
+                            index: result.len(),
                             field_name,
+                            value_type: $crate::type_name(stringify!($cast)),
                             keys: cli_names,
+                            aliases: Vec::new(),
+                            hidden_aliases: Vec::new(),
                             description: description.to_string(),
+                            long_description,
+                            hide_short_help: false,
+                            hidden: false,
+                            deprecated: None,
+                            missing_value: None,
+                            none_value: None,
+                            default_display,
+                            allow_hyphen_values: false,
+                            duplicates: None,
                             optional,
                             position,
+                            section: section.clone(),
                             value: actual_default
                         };
 
-                        result.insert(argument.field_name.clone(), argument);
-                    )*
+                        $($( $crate::argument_setting!(argument; $setting $(= $setting_value)?); )*)?
+                        // Same for arguments that may be given without a value.
+                        if argument.missing_value.is_some() {
+                            argument.optional = true;
+                        }
 
-                    return Ok(result);
-                }
+                        if argument.none_value.is_some() && !parser.is_optional() {
+                            return Err(format!("Argument `{}` sets `none_value` but {} can't be absent.", argument.field_name, argument.value_type).into());
+                        }
+
+                        result.push(argument);
+                    }
+                )*
 
-                fn sort_arguments_vector(arguments_vector: &mut Vec<(&String, &Argument<ArgumentType>)>) {
-                    arguments_vector.sort_by(|(n, v), (n2, v2)| {
-                        let pos = v.position.unwrap_or(usize::MAX);
-                        let pos2 = v2.position.unwrap_or(usize::MAX);
+                // Aliases are only known once the settings ran, so names they share can't be caught at compile time.
+                for (i, argument) in result.iter().enumerate() {
+                    for key in argument.all_keys() {
+                        if let Some(other) = result[..i].iter().find(|other| other.matches_key(key)) {
+                            return Err(format!(
+                                "Argument `{}` uses the name `{}` of argument `{}`.", argument.field_name, key, other.field_name
+                            ).into());
+                        }
+                    }
+                }
 
-                        let opt = v.optional;
-                        let opt2 = v2.optional;
+                return Ok(result);
+            }
 
-                        let optional = if opt { "1" } else { "0" };
-                        let optional2 = if opt2 { "1" } else { "0" };
+            pub fn arguments(order: ArgumentOrder) -> Result<Vec<Argument<ArgumentType>>, Box<dyn Error>> {
+                let mut arguments = Self::get_arguments()?;
+                arguments.sort_by(|a, b| order.compare(a, b));
+                Ok(arguments)
+            }
 
-                        format!("{}{}{}", optional, pos, n).cmp(
-                            &format!("{}{}{}", optional2, pos2, n2)
-                        )
-                    });
+            pub fn sections() -> Vec<String> {
+                // Section names in declaration order, a section declared again is listed once.
+                let mut sections: Vec<String> = Vec::new();
+                for argument in Self::get_arguments().unwrap_or_default() {
+                    if let Some(section) = argument.section {
+                        if !sections.contains(&section) {
+                            sections.push(section);
+                        }
+                    }
                 }
+                sections
+            }
 
-                pub fn show_help(options: Option<HelpOptions>) -> Result<(), Box<dyn Error>> {
-                    let help_message = Self::help(options)?;
-                    println!("{}", help_message);
-                    Ok(())
+            pub fn help_text() -> HelpText {
+                let about: Option<String> = None;
+                $( let about = Some($about.to_string()); )?
+
+                let long_about: Option<String> = None;
+                $( let long_about = Some($long_about.to_string()); )?
+
+                let examples: Vec<Example> = vec![$($(
+                    Example {
+                        command: $example_command.to_string(),
+                        explanation: $example_explanation.to_string()
+                    },
+                )*)?];
+
+                let after_help: Option<String> = None;
+                $( let after_help = Some($after_help.to_string()); )?
+
+                HelpText {
+                    about,
+                    long_about,
+                    examples,
+                    after_help
                 }
+            }
+
+            pub fn show_help(options: Option<HelpOptions>) -> Result<(), Box<dyn Error>> {
+                <Self as $crate::ArgumentConfig>::show_help(options)
+            }
+
+            fn listed_arguments<'a>(arguments: &'a [Argument<ArgumentType>], options: &HelpOptions) -> Vec<&'a Argument<ArgumentType>> {
+                let mut arguments_vector: Vec<&Argument<ArgumentType>> = arguments.iter()
+                    .filter(|argument| !argument.hidden)
+                    .filter(|argument| options.detail == HelpDetail::Long || !argument.hide_short_help)
+                    .collect();
+                arguments_vector.sort_by(|a, b| options.order.compare(a, b));
+                arguments_vector
+            }
 
-                pub fn help(options: Option<HelpOptions>) -> Result<String, Box<dyn Error>> {
-                    let mut help_message = String::new();
-
-                    let options = options.unwrap_or(HelpOptions::default());
-                    let arguments = Self::get_arguments()?;
-                    let longest_name: usize = arguments.values().into_iter().map(
-                        |s| {
-                            s.pretty_name().len()
-                        }).max().unwrap_or(0);
-        
-                    help_message.push_str(
-                        format!("Usage: {}\n", $executable_name).as_str()
+            fn usage_line(arguments_vector: &[&Argument<ArgumentType>]) -> String {
+                let mut usage = format!("Usage: {}", $executable_name);
+
+                // Positional arguments are always shown in the order they are expected.
+                let mut usage_vector = arguments_vector.to_vec();
+                usage_vector.sort_by_key(|argument| argument.position.unwrap_or(usize::MAX));
+
+                // TODO: Add argument values to usage
+                for argument in &usage_vector {
+                    usage.push_str(
+                        if argument.optional {
+                            format!(" [{}]", argument.pretty_name())
+                        } else {
+                            format!(" <{}>", argument.pretty_name())
+                        }.as_str()
                     );
+                }
 
-                    // Sort arguments by position
-                    let mut arguments_vector: Vec<(&String, &Argument<ArgumentType>)> = arguments.iter().collect();
-                    Self::sort_arguments_vector(&mut arguments_vector);
-                    
-                    // TODO: Add argument values to usage
-                    for (_, argument) in &arguments_vector {
-                        help_message.push_str(
-                            if argument.optional {
-                                format!(" [{}]", argument.pretty_name())
-                            } else {
-                                format!(" <{}>", argument.pretty_name())
-                            }.as_str()
-                        );
+                usage
+            }
+
+            pub fn usage(options: Option<HelpOptions>) -> Result<String, Box<dyn Error>> {
+                let options = options.unwrap_or(HelpOptions::default());
+                let arguments = Self::get_arguments()?;
+                Ok(Self::usage_line(&Self::listed_arguments(&arguments, &options)))
+            }
+
+            pub fn render_error(error: &(dyn Error + 'static), args: &[String]) -> String {
+                let usage = Self::usage(None).unwrap_or_default();
+                match error.downcast_ref::<ParseError>() {
+                    Some(error) => error.render(args, &usage),
+                    None => format!("error: {}\n\n{}\n", error, usage)
+                }
+            }
+
+            pub fn help(options: Option<HelpOptions>) -> Result<String, Box<dyn Error>> {
+                let mut help_message = String::new();
+
+                let options = options.unwrap_or(HelpOptions::default());
+                let arguments = Self::get_arguments()?;
+                let longest_name: usize = arguments.iter().filter(|s| !s.hidden).map(
+                    |s| {
+                        s.pretty_name().len()
+                    }).max().unwrap_or(0);
+
+                let help_text = Self::help_text();
+                let indent = String::from(" ").repeat(options.indent_length);
+
+                let long_about = match options.detail {
+                    HelpDetail::Short => &None,
+                    HelpDetail::Long => &help_text.long_about
+                };
+                for paragraph in [&help_text.about, long_about].into_iter().flatten() {
+                    help_message.push_str(format!("{}\n\n", paragraph).as_str());
+                }
+
+                let arguments_vector = Self::listed_arguments(&arguments, &options);
+                help_message.push_str(Self::usage_line(&arguments_vector).as_str());
+                help_message.push_str("\n");
+
+                // Arguments without a section are listed first, under the default headings.
+                let mut groups: Vec<(String, Vec<&Argument<ArgumentType>>)> = Vec::new();
+                let (positionals, keywords): (Vec<_>, Vec<_>) = arguments_vector.iter()
+                    .copied()
+                    .filter(|argument| argument.section.is_none())
+                    .partition(|argument| options.separate_positionals && argument.position.is_some());
+                groups.push((options.positionals_heading.clone(), positionals));
+                groups.push((options.options_heading.clone(), keywords));
+
+                for section in Self::sections() {
+                    let members = arguments_vector.iter()
+                        .copied()
+                        .filter(|argument| argument.section.as_ref() == Some(&section))
+                        .collect();
+                    groups.push((section, members));
+                }
+
+                for (heading, members) in groups {
+                    if members.is_empty() {
+                        continue;
                     }
-                    help_message.push_str("\n\n");
 
-                    let description_offset_no_name = options.indent_length + longest_name + options.description_offset;
+                    help_message.push_str(format!("\n{}:\n", heading).as_str());
+                    for argument in members {
+                        help_message.push_str(Self::help_entry(argument, &options, longest_name).as_str());
+                    }
+                }
 
-                    for (field_name, argument) in arguments_vector {
-                        help_message.push_str(
-                            format!("{}{:<width$}", 
-                                String::from(" ").repeat(options.indent_length), 
-                                argument.pretty_name(), 
-                                width = (longest_name + options.description_offset
-                            )
-                        ).as_str());
-        
-                        // Split the description into multiple lines if it's too long
-                        let mut desc_split: Vec<String> = Vec::new();
-        
-                        let mut too_long = false;
-                        for (i, ch) in argument.description.chars().enumerate() {
-                            if i % options.description_max_length == 0 {
-                                too_long = true;
-                            }
-                            if (too_long
-                                && options.split_at.matches(ch))
-                                || i == 0 {
-                                desc_split.push(String::new());
-                                too_long = false;
-                            }
-                            desc_split.last_mut().unwrap().push(ch);
-                        }
-        
-                        // Print description parts
-                        let mut desc_split_iter = desc_split.iter();
+                if !help_text.examples.is_empty() {
+                    help_message.push_str("\nExamples:\n");
+                    for example in &help_text.examples {
                         help_message.push_str(
-                            if let Some(d1) = desc_split_iter.nth(0) {
-                                format!("{}\n", d1)
-                            } else {
-                                format!("\n")
-                            }.as_str()
+                            format!("{}{} {}\n{}{}{}\n",
+                                indent, $executable_name, example.command,
+                                indent, indent, example.explanation
+                            ).as_str()
                         );
-                        
-                        for d in desc_split_iter.into_iter() {
-                            let padding = String::from(" ").repeat((description_offset_no_name - 1) + options.description_newline_extra_padding);
-                            help_message.push_str(
-                                format!("{}{}\n", padding, d).as_str()
-                            );
+                    }
+                }
+
+                if let Some(after_help) = &help_text.after_help {
+                    help_message.push_str(format!("\n{}\n", after_help).as_str());
+                }
+
+                Ok(help_message)
+            }
+
+            fn help_entry(argument: &Argument<ArgumentType>, options: &HelpOptions, longest_name: usize) -> String {
+                let mut entry = String::new();
+
+                let description_offset_no_name = options.indent_length + longest_name + options.description_offset;
+
+                entry.push_str(
+                    format!("{}{:<width$}", 
+                        String::from(" ").repeat(options.indent_length), 
+                        argument.pretty_name(), 
+                        width = (longest_name + options.description_offset
+                    )
+                ).as_str());
+
+                let mut description = argument.description.clone();
+                let mut long_description = match options.detail {
+                    HelpDetail::Short => String::new(),
+                    HelpDetail::Long => argument.long_description.clone()
+                };
+                // The default closes whatever text is shown.
+                if let Some(default) = &argument.default_display {
+                    let last = if long_description.is_empty() { &mut description } else { &mut long_description };
+                    *last = format!("{} [default: {}]", last, default).trim_start().to_string();
+                }
+
+                let mut desc_split = Self::split_description(&description, options);
+                if !long_description.is_empty() {
+                    for (i, paragraph) in long_description.split("\n\n").enumerate() {
+                        // An empty part is printed as a blank line between paragraphs.
+                        if i > 0 {
+                            desc_split.push(String::new());
                         }
+                        // Continuation lines start with the split character, so line up with them.
+                        desc_split.extend(Self::split_description(&format!(" {}", paragraph), options));
                     }
+                }
 
-                    Ok(help_message)
+                // Print description parts
+                let mut desc_split_iter = desc_split.iter();
+                entry.push_str(
+                    if let Some(d1) = desc_split_iter.nth(0) {
+                        format!("{}\n", d1)
+                    } else {
+                        format!("\n")
+                    }.as_str()
+                );
+
+                for d in desc_split_iter.into_iter() {
+                    if d.is_empty() {
+                        entry.push('\n');
+                        continue;
+                    }
+                    let padding = String::from(" ").repeat((description_offset_no_name - 1) + options.description_newline_extra_padding);
+                    entry.push_str(
+                        format!("{}{}\n", padding, d).as_str()
+                    );
                 }
-                
-                fn cast_value<T: FromStr>(value: &str) -> Result<T, Box<dyn Error>> {
-                    match value.parse::<T>() {
-                        Ok(value) => Ok(value),
-                        Err(_) => Err(format!("Invalid value for argument: {}", value).into())
+
+                entry
+            }
+
+            fn split_description(description: &str, options: &HelpOptions) -> Vec<String> {
+                // Split the description into multiple lines if it's too long
+                let mut desc_split: Vec<String> = Vec::new();
+
+                let mut too_long = false;
+                for (i, ch) in description.chars().enumerate() {
+                    if i % options.description_max_length == 0 {
+                        too_long = true;
+                    }
+                    if (too_long
+                        && options.split_at.matches(ch))
+                        || i == 0 {
+                        desc_split.push(String::new());
+                        too_long = false;
                     }
+                    desc_split.last_mut().unwrap().push(ch);
                 }
 
-                pub fn get(key: &str) -> CLIValue {
-                    let args: Vec<String> = std::env::args().collect();
-                    let mut args = args.into_iter();
-                    
-                    // Skip executable name
-                    args.next();
-
-                    for arg in args {
-                        if arg == key {
-                            return CLIValue::Bool(true);
-                        } else if let Some((arg_key, arg_value)) = arg.split_once("=") {
-                            if arg_key == key {
-                                return CLIValue::String(arg_value.to_string());
-                            }
+                desc_split
+            }
+
+            pub fn requested_help(args: &[String], field_name: &str) -> Result<Option<HelpDetail>, Box<dyn Error>> {
+                let arguments = Self::get_arguments()?;
+                let argument = match arguments.iter().find(|argument| argument.field_name == field_name) {
+                    Some(argument) => argument,
+                    None => return Err(format!("Unknown argument field `{}`.", field_name).into())
+                };
+
+                // The last help flag given decides the detail.
+                Ok(args.iter().skip(1)
+                    .filter(|arg| argument.matches_key(arg))
+                    .last()
+                    .map(|key| HelpDetail::for_key(key)))
+            }
+
+            fn invalid_value(argument: &Argument<ArgumentType>, value: &std::ffi::OsStr, source: ValueError, index: usize) -> ParseError {
+                ParseError::InvalidValue {
+                    field_name: argument.field_name.clone(),
+                    expected: argument.value_type.clone(),
+                    value: value.to_string_lossy().to_string(),
+                    source,
+                    index
+                }
+            }
+
+            fn raw_suffix(raw: &std::ffi::OsStr, offset: usize) -> &std::ffi::OsStr {
+                let bytes = raw.as_encoded_bytes();
+                // SAFETY: `offset` is the length of a key and separator that matched as text, so the raw
+                // argument starts with that valid UTF-8 and is split right after it.
+                unsafe { std::ffi::OsStr::from_encoded_bytes_unchecked(&bytes[offset..]) }
+            }
+
+            pub fn get(key: &str) -> CLIValue {
+                let args: Vec<String> = std::env::args_os().map(|arg| arg.to_string_lossy().to_string()).collect();
+                let mut args = args.into_iter();
+
+                // Skip executable name
+                args.next();
+
+                // Any name of the argument `key` belongs to is accepted.
+                let arguments = Self::get_arguments().unwrap_or_default();
+                let argument = arguments.iter().find(|argument| argument.matches_key(key));
+                let matches = |name: &str| match argument {
+                    Some(argument) => argument.matches_key(name),
+                    None => name == key
+                };
+
+                for arg in args {
+                    if matches(&arg) {
+                        return CLIValue::Bool(true);
+                    } else if let Some((arg_key, arg_value)) = arg.split_once("=") {
+                        if matches(arg_key) {
+                            return CLIValue::String(arg_value.to_string());
+                        }
+                    }
+                }
+
+                CLIValue::None
+            }
+
+            pub fn parse_custom(args: Vec<String>) -> Result<Self, Box<dyn Error>> {
+                <Self as $crate::ArgumentConfig>::parse_custom(args)
+            }
+
+            pub fn parse_custom_os(args: Vec<std::ffi::OsString>) -> Result<Self, Box<dyn Error>> {
+                <Self as $crate::ArgumentConfig>::parse_custom_os(args)
+            }
+
+            pub fn parse_custom_with_warnings(args: Vec<String>) -> Result<(Self, Vec<ParseWarning>), Box<dyn Error>> {
+                Self::parse_custom_with(args, None)
+            }
+
+            fn abbreviation_candidates<'a>(key: &str, arguments: &'a [Argument<ArgumentType>], style: &ParseStyle) -> Vec<&'a String> {
+                if !key.starts_with("--") || key == "--" || arguments.iter().any(|argument| argument.matches_key_with(key, style)) {
+                    return Vec::new();
+                }
+
+                // Only documented names can be abbreviated, one candidate per argument.
+                arguments.iter()
+                    .filter(|argument| !argument.hidden)
+                    .filter_map(|argument| argument.keys.iter()
+                        .chain(argument.aliases.iter())
+                        .find(|k| k.starts_with("--") && k.get(..key.len()).is_some_and(|prefix| style.keys_match(prefix, key))))
+                    .collect()
+            }
+
+            fn resolve_abbreviations(cliargs: &mut [String], raw_args: &mut [std::ffi::OsString], arguments: &[Argument<ArgumentType>], style: &ParseStyle, errors: &mut Vec<ParseError>, collect: bool) -> Result<(), ParseError> {
+                for (i, carg) in cliargs.iter_mut().enumerate() {
+                    let key = style.split_value(carg).map(|(key, _)| key).unwrap_or(carg);
+
+                    let candidates = Self::abbreviation_candidates(key, arguments, style);
+                    match candidates.as_slice() {
+                        [] => {},
+                        [full_key] => {
+                            // Keep the separator and value as given.
+                            let mut raw = std::ffi::OsString::from(full_key.as_str());
+                            raw.push(Self::raw_suffix(&raw_args[i], key.len()));
+                            raw_args[i] = raw;
+                            *carg = format!("{}{}", full_key, &carg[key.len()..]);
+                        },
+                        _ => {
+                            Self::report_error(ParseError::AmbiguousOption {
+                                key: key.to_string(),
+                                candidates: candidates.iter().map(|k| k.to_string()).collect(),
+                                index: i + 1
+                            }, errors, collect)?;
                         }
                     }
+                }
+
+                Ok(())
+            }
+
+            fn report_error(error: ParseError, errors: &mut Vec<ParseError>, collect: bool) -> Result<(), ParseError> {
+                // Either fail right away or keep going and report everything at the end.
+                if collect {
+                    errors.push(error);
+                    Ok(())
+                } else {
+                    Err(error)
+                }
+            }
 
-                    CLIValue::None
+            fn collect_error<T>(result: Result<T, ParseError>, errors: &mut Vec<ParseError>, collect: bool) -> Result<Option<T>, ParseError> {
+                match result {
+                    Ok(value) => Ok(Some(value)),
+                    Err(error) => Self::report_error(error, errors, collect).map(|_| None)
                 }
+            }
 
-                pub fn parse_custom(args: Vec<String>) -> Result<Self, Box<dyn Error>> {
-                    let cliargs: Vec<String> = args.into_iter().skip(1).collect();
+            fn is_value(arg: &str, argument: &Argument<ArgumentType>, arguments: &[Argument<ArgumentType>], style: &ParseStyle) -> bool {
+                if !style.is_option(arg) {
+                    return true;
+                }
 
-                    let setup_arguments = Self::get_arguments()?;
+                let key = style.split_value(arg).map(|(key, _)| key).unwrap_or(arg);
+                if arguments.iter().any(|argument| argument.matches_key_with(key, style)) {
+                    return false;
+                }
 
-                    let result = Self {
-                        $(
-                            $(
-                                $(#[$internal_attr])*
-                                $internal_name: $internal_value,
-                            )*
-                        )?
+                // Negative numbers are values unless an option has that name.
+                argument.allow_hyphen_values || $crate::is_number(arg)
+            }
+
+            pub fn parse_custom_with(args: Vec<String>, options: Option<ParseOptions>) -> Result<(Self, Vec<ParseWarning>), Box<dyn Error>> {
+                <Self as $crate::ArgumentConfig>::parse_custom_with(args, options)
+            }
 
+            pub fn parse_custom_os_with(args: Vec<std::ffi::OsString>, options: Option<ParseOptions>) -> Result<(Self, Vec<ParseWarning>), Box<dyn Error>> {
+                let options = options.unwrap_or_default();
+                let mut raw_args: Vec<std::ffi::OsString> = args.into_iter().skip(1).collect();
+                // Keys are matched as text, values are taken from the raw arguments.
+                let mut cliargs: Vec<String> = raw_args.iter().map(|arg| arg.to_string_lossy().to_string()).collect();
+
+                let setup_arguments = Self::get_arguments()?;
+                let collect = options.collect_errors;
+                // Arguments with their own policy ignore these, the parse options override the config.
+                let config_duplicates: Option<DuplicatePolicy> = None;
+                $( let config_duplicates = Some($duplicates); )?
+                let default_duplicates = options.duplicates.or(config_duplicates).unwrap_or_default();
+                let mut errors: Vec<ParseError> = Vec::new();
+                if options.abbreviations {
+                    Self::resolve_abbreviations(&mut cliargs, &mut raw_args, &setup_arguments, &options.style, &mut errors, collect)?;
+                }
+                let style = &options.style;
+                let mut warnings: Vec<ParseWarning> = Vec::new();
+                // Indexes of tokens taken as positional values.
+                #[allow(unused_mut)]
+                let mut positional_values: Vec<usize> = Vec::new();
+                // Fields given on the command line, derived defaults leave them alone.
+                #[allow(unused_mut)]
+                let mut given: Vec<&str> = Vec::new();
+
+                // Each field is computed into a local of its name, with the `cfg` attributes of the field.
+                $($(
+                    $(#[cfg($internal_cfg)])*
+                    let $internal_name = $internal_value;
+                )*)?
+
+                $(
+                    $(#[cfg($cfg)])*
+                    let $name = {
+                        // Unwrap here should be safe
+                        let setup_arg = setup_arguments.iter()
+                            .find(|argument| argument.field_name == stringify!($name))
+                            .unwrap();
+
+                        #[allow(unused_assignments)]
+                        let mut value = ArgumentType::None;
+                        // The command line token the value came from.
+                        #[allow(unused_mut)]
+                        let mut used: Option<String> = None;
+
+                        let cliargs = cliargs.clone();
+
+                        let parse = |raw: &std::ffi::OsStr, index: usize| -> Result<$cast, ParseError> {
+                            if setup_arg.none_value.as_deref().is_some_and(|none| raw == std::ffi::OsStr::new(none)) {
+                                // Only optional types may set `none_value`, their default is absent.
+                                return Ok(<$cast>::default());
+                            }
+                            (&&&ValueParser::<$cast>(std::marker::PhantomData)).parse_value(raw, style)
+                                .map_err(|source| Self::invalid_value(setup_arg, raw, source, index))
+                        };
+
+                        // Check positional arguments
                         $(
-                        $name: {
-                            // Unwrap here should be safe
-                            let setup_arg = setup_arguments.get(stringify!($name)).unwrap();
-                            
-                            #[allow(unused_assignments)]
-                            let mut value = ArgumentType::None;
-
-                            let cliargs = cliargs.clone();
-                            
-                            // Check positional arguments
-                            $(
-                            if let Some(arg) = cliargs.get($cli_position) {
-                                if arg.starts_with("-") {
-                                    if !setup_arg.optional {
-                                        return Err(format!("Expected required positional argument `{}`, found keyword argument `{}`.", 
-                                            stringify!($name),
-                                            arg
-                                        ).into());   
-                                    }
-                                } else {
-                                    value = ArgumentType::$name(Self::cast_value::<$cast>(arg)?);
+                        match cliargs.get($cli_position) {
+                            Some(arg) if Self::is_value(arg, setup_arg, &setup_arguments, style) => {
+                                positional_values.push($cli_position);
+                                let raw = raw_args[$cli_position].as_os_str();
+                                let cast = parse(raw, $cli_position + 1);
+                                if let Some(v) = Self::collect_error(cast, &mut errors, collect)? {
+                                    value = ArgumentType::$name(v);
                                 }
+                                used = Some(arg.clone());
+                            },
+                            Some(arg) if !setup_arg.optional => Self::report_error(ParseError::ExpectedPositional {
+                                field_name: stringify!($name).to_string(),
+                                found: arg.clone(),
+                                index: $cli_position + 1
+                            }, &mut errors, collect)?,
+                            None if !setup_arg.optional => Self::report_error(ParseError::MissingRequired {
+                                field_name: stringify!($name).to_string()
+                            }, &mut errors, collect)?,
+                            _ => {}
+                        }
+                        )?
+
+                        // Check keyword arguments
+                        let duplicates = setup_arg.duplicates.unwrap_or(default_duplicates);
+                        let is_flag = (&&&ValueParser::<$cast>(std::marker::PhantomData)).is_flag();
+                        let mut keyword_given = false;
+                        for (i, carg) in cliargs.iter().enumerate() {
+                            if !style.is_option(carg) {
+                                continue;
                             }
-                            )?
 
-                            // Check keyword arguments
-                            for carg in cliargs.iter() {
-                                if !carg.starts_with("-") {
-                                    continue;
+                            let given: Option<(&str, &std::ffi::OsStr)> = if setup_arg.matches_key_with(carg, style) {
+                                // A bare key takes the declared missing value, flags become `true`
+                                match setup_arg.missing_value.as_deref() {
+                                    Some(missing_value) => Some((carg, std::ffi::OsStr::new(missing_value))),
+                                    None if is_flag => Some((carg, std::ffi::OsStr::new("true"))),
+                                    None => {
+                                        Self::report_error(ParseError::MissingValue {
+                                            field_name: setup_arg.field_name.clone(),
+                                            key: carg.to_string(),
+                                            index: i + 1
+                                        }, &mut errors, collect)?;
+                                        continue;
+                                    }
                                 }
-
-                                $(
-                                if carg == $cli_name {
-                                    // Cast value to bool
-                                    value = ArgumentType::$name(Self::cast_value::<$cast>("true")?);
-                                } else if let Some((arg_key, arg_value)) = carg.split_once("=") {
-                                    if arg_key == $cli_name {
-                                        value = ArgumentType::$name(Self::cast_value::<$cast>(arg_value)?);
+                            } else {
+                                style.split_value(carg)
+                                    .filter(|(arg_key, _)| setup_arg.matches_key_with(arg_key, style))
+                                    .map(|(arg_key, arg_value)| (arg_key, Self::raw_suffix(&raw_args[i], carg.len() - arg_value.len())))
+                            };
+
+                            if let Some((arg_key, arg_value)) = given {
+                                if keyword_given {
+                                    match duplicates {
+                                        DuplicatePolicy::Override => {},
+                                        DuplicatePolicy::KeepFirst => continue,
+                                        DuplicatePolicy::Error => {
+                                            Self::report_error(ParseError::DuplicateOption {
+                                                field_name: setup_arg.field_name.clone(),
+                                                key: arg_key.to_string(),
+                                                index: i + 1
+                                            }, &mut errors, collect)?;
+                                            continue;
+                                        }
                                     }
                                 }
-                                )?
+
+                                let cast = parse(arg_value, i + 1);
+                                if let Some(v) = Self::collect_error(cast, &mut errors, collect)? {
+                                    value = ArgumentType::$name(v);
+                                }
+                                used = Some(arg_key.to_string());
+                                keyword_given = true;
                             }
+                        }
 
-                            // --------------------
+                        // Positionals were checked above, options without a default have to be given too.
+                        if setup_arg.position.is_none() && !setup_arg.optional && !keyword_given {
+                            Self::report_error(ParseError::MissingRequired {
+                                field_name: stringify!($name).to_string()
+                            }, &mut errors, collect)?;
+                        }
 
-                            let mut result: $cast = <$cast>::default();
+                        if let (Some(note), Some(key)) = (&setup_arg.deprecated, used) {
+                            warnings.push(ParseWarning::Deprecated {
+                                field_name: setup_arg.field_name.clone(),
+                                key,
+                                note: note.clone()
+                            });
+                        }
 
-                            // Default from setup
-                            let default: ArgumentType = setup_arg.value.clone();
-                            if let ArgumentType::$name(v) = default {
-                                result = v;
-                            }
+                        // --------------------
 
-                            if let ArgumentType::$name(v) = value {
-                                result = v;
-                            }
+                        if !matches!(value, ArgumentType::None) {
+                            given.push(stringify!($name));
+                        }
 
-                            result
-                        },
-                        )*
+                        let mut result: $cast = <$cast>::default();
+
+                        // Default from setup
+                        let default: ArgumentType = setup_arg.value.clone();
+                        if let ArgumentType::$name(v) = default {
+                            result = v;
+                        }
+
+                        if let ArgumentType::$name(v) = value {
+                            result = v;
+                        }
+
+                        result
                     };
+                )*
 
-                    Ok(result)
+                #[allow(unused_mut)]
+                let mut result = Self {
+                    $($( $(#[cfg($internal_cfg)])* $internal_name, )*)?
+                    $( $(#[cfg($cfg)])* $name, )*
+                };
+
+                // Derived defaults run once the fields they read are final, cycles don't compile.
+                let mut pending: Vec<(&str, &[&str])> = DERIVED.to_vec();
+                while !pending.is_empty() {
+                    let ready = pending.iter()
+                        .position(|(_, dependencies)| dependencies.iter().all(|dependency| pending.iter().all(|(name, _)| name != dependency)))
+                        .unwrap();
+                    let (name, _) = pending.remove(ready);
+                    if given.contains(&name) {
+                        continue;
+                    }
+                    $($(
+                        if name == stringify!($derived_name) {
+                            let value = {
+                                $( let $derived_dependency = &result.$derived_dependency; )*
+                                $derived_value
+                            };
+                            result.$derived_name = value;
+                        }
+                    )*)?
                 }
 
-                pub fn parse() -> Result<Self, Box<dyn Error>> {
-                    let args: Vec<String> = std::env::args().collect();
-                    Self::parse_custom(args)
+                // Unknown options are only reported when collecting errors, otherwise they are ignored.
+                for (i, carg) in cliargs.iter().enumerate() {
+                    if !collect || !style.is_option(carg) || positional_values.contains(&i) {
+                        continue;
+                    }
+
+                    let key = style.split_value(carg).map(|(key, _)| key).unwrap_or(carg);
+                    let known = setup_arguments.iter().any(|argument| argument.matches_key_with(carg, style) || argument.matches_key_with(key, style));
+                    // Ambiguous abbreviations were already reported.
+                    let ambiguous = options.abbreviations && Self::abbreviation_candidates(key, &setup_arguments, style).len() > 1;
+                    if !known && !ambiguous {
+                        Self::report_error(ParseError::UnknownOption { key: key.to_string(), index: i + 1 }, &mut errors, collect)?;
+                    }
                 }
 
-                $(
-                    $( $then_execute )*
-                )?
+                if !errors.is_empty() {
+                    return Err(ParseError::Multiple(errors).into());
+                }
+
+                Ok((result, warnings))
             }
 
-            impl std::fmt::Display for $struct_name {
-                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                    write!(f, "{:?}", self)
-                }
+            pub fn parse() -> Result<Self, Box<dyn Error>> {
+                <Self as $crate::ArgumentConfig>::parse()
+            }
+
+            pub fn parse_os() -> Result<Self, Box<dyn Error>> {
+                <Self as $crate::ArgumentConfig>::parse_os()
+            }
+
+            pub fn parse_with_warnings() -> Result<(Self, Vec<ParseWarning>), Box<dyn Error>> {
+                <Self as $crate::ArgumentConfig>::parse_with_warnings()
+            }
+
+            $(
+                $( $then_execute )*
+            )?
+        }
+
+        impl $crate::ArgumentConfig for $struct_name {
+            type Value = ArgumentType;
+
+            const EXECUTABLE_NAME: &'static str = $executable_name;
+
+            fn arguments(order: ArgumentOrder) -> Result<Vec<Argument<ArgumentType>>, Box<dyn Error>> {
+                $struct_name::arguments(order)
+            }
+
+            fn sections() -> Vec<String> {
+                $struct_name::sections()
+            }
+
+            fn help_text() -> HelpText {
+                $struct_name::help_text()
+            }
+
+            fn usage(options: Option<HelpOptions>) -> Result<String, Box<dyn Error>> {
+                $struct_name::usage(options)
+            }
+
+            fn help(options: Option<HelpOptions>) -> Result<String, Box<dyn Error>> {
+                $struct_name::help(options)
+            }
+
+            fn requested_help(args: &[String], field_name: &str) -> Result<Option<HelpDetail>, Box<dyn Error>> {
+                $struct_name::requested_help(args, field_name)
+            }
+
+            fn render_error(error: &(dyn Error + 'static), args: &[String]) -> String {
+                $struct_name::render_error(error, args)
+            }
+
+            fn parse_custom_os_with(args: Vec<std::ffi::OsString>, options: Option<ParseOptions>) -> Result<(Self, Vec<ParseWarning>), Box<dyn Error>> {
+                $struct_name::parse_custom_os_with(args, options)
             }
         }
     }
 }
+
+// The struct of `config_setup!`. Argument fields are `pub` unless they name a restriction like `pub(crate)`,
+// they come in as `[attributes] [name pub(restriction)]: type,`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! config_struct {
+    (
+        $(#[$attr:meta])*
+        $public_flag:vis struct $struct_name:ident {
+            $( [$(#[$($field_attr:tt)*])*] [$name:ident $(pub $(($($restrict:tt)+))?)?]: $cast:ty, )*
+        }
+        { $($internal_fields:tt)* }
+    ) => {
+        $(#[$attr])*
+        $public_flag struct $struct_name {
+            $(
+                $(#[$($field_attr)*])*
+                pub $($(($($restrict)+))?)? $name: $cast,
+            )*
+
+            $($internal_fields)*
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! argument_setting {
+    ($argument:ident; long_help = $value:expr) => {
+        $argument.long_description = $value.to_string();
+    };
+    ($argument:ident; section = $value:expr) => {
+        $argument.section = Some($value.to_string());
+    };
+    ($argument:ident; hide_short_help) => {
+        $argument.hide_short_help = true;
+    };
+    ($argument:ident; aliases = $aliases:expr) => {
+        $argument.aliases = $aliases.iter().map(|alias| alias.to_string()).collect();
+    };
+    ($argument:ident; hidden_aliases = $aliases:expr) => {
+        $argument.hidden_aliases = $aliases.iter().map(|alias| alias.to_string()).collect();
+    };
+    ($argument:ident; missing_value = $value:expr) => {
+        $argument.missing_value = Some($value.to_string());
+    };
+    ($argument:ident; none_value = $value:expr) => {
+        $argument.none_value = Some($value.to_string());
+    };
+    ($argument:ident; allow_hyphen_values) => {
+        $argument.allow_hyphen_values = true;
+    };
+    ($argument:ident; duplicates = $policy:expr) => {
+        $argument.duplicates = Some($policy);
+    };
+    ($argument:ident; hidden) => {
+        $argument.hidden = true;
+    };
+    ($argument:ident; deprecated = $note:expr) => {
+        $argument.deprecated = Some($note.to_string());
+    };
+    ($argument:ident; $setting:ident $(= $value:expr)?) => {
+        compile_error!(concat!("Unknown argument setting `", stringify!($setting), "`."));
+    };
+}
+
+// The doc comment lines among a field's attributes, as an array.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! doc_lines {
+    (@lines [$($lines:expr),*]) => {
+        [$($lines),*]
+    };
+    (@lines [$($lines:expr),*] #[doc = $line:literal] $($rest:tt)*) => {
+        $crate::doc_lines!(@lines [$($lines,)* $line] $($rest)*)
+    };
+    (@lines [$($lines:expr),*] #[$($other:tt)*] $($rest:tt)*) => {
+        $crate::doc_lines!(@lines [$($lines),*] $($rest)*)
+    };
+    ($($attrs:tt)*) => {
+        $crate::doc_lines!(@lines [] $($attrs)*)
+    };
+}
+
+// Hands the declaration of `config_setup!` to `config_impl!` with the `cfg` attributes of every field in a slot
+// of their own, and its doc comment, the other attributes only go on the struct. Fields come in as
+// `{kind [cfg] [doc] [unsorted attributes] ...}` and have one attribute sorted per step, except for fields with
+// nothing but doc comments, which take one step for four of them to stay under the recursion limit.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! config_cfg {
+    (
+        {$($head:tt)*} {$($derived:tt)*} {$($tail:tt)*}
+        [
+            $( {field [$($cfg:tt)*] [$($doc:tt)*] [] ($($section:tt)*) $($field:tt)*} )*
+            $( {internal [$($internal_cfg:tt)*] [$($internal_doc:tt)*] [] $($internal:tt)*} )*
+        ]
+    ) => {
+        $crate::config_impl! {
+            $($head)*
+            $( [$($cfg)*] $($section)* $($doc)* $($field)* )*
+            $($derived)*
+            @internal { $( [$($internal_cfg)*] $($internal)* )* }
+            $($tail)*
+        }
+    };
+    (
+        {$($head:tt)*} {$($derived:tt)*} {$($tail:tt)*} [$($done:tt)*]
+        {$kind_0:ident [$($cfg_0:tt)*] [$($doc_0:tt)*] [$(#[doc = $line_0:literal])*] $($field_0:tt)*}
+        {$kind_1:ident [$($cfg_1:tt)*] [$($doc_1:tt)*] [$(#[doc = $line_1:literal])*] $($field_1:tt)*}
+        {$kind_2:ident [$($cfg_2:tt)*] [$($doc_2:tt)*] [$(#[doc = $line_2:literal])*] $($field_2:tt)*}
+        {$kind_3:ident [$($cfg_3:tt)*] [$($doc_3:tt)*] [$(#[doc = $line_3:literal])*] $($field_3:tt)*}
+        $($rest:tt)*
+    ) => {
+        $crate::config_cfg! {
+            {$($head)*} {$($derived)*} {$($tail)*}
+            [
+                $($done)*
+                {$kind_0 [$($cfg_0)*] [$($doc_0)* $(#[doc = $line_0])*] [] $($field_0)*}
+                {$kind_1 [$($cfg_1)*] [$($doc_1)* $(#[doc = $line_1])*] [] $($field_1)*}
+                {$kind_2 [$($cfg_2)*] [$($doc_2)* $(#[doc = $line_2])*] [] $($field_2)*}
+                {$kind_3 [$($cfg_3)*] [$($doc_3)* $(#[doc = $line_3])*] [] $($field_3)*}
+            ]
+            $($rest)*
+        }
+    };
+    (
+        {$($head:tt)*} {$($derived:tt)*} {$($tail:tt)*} [$($done:tt)*]
+        {$kind:ident [$($cfg:tt)*] [$($doc:tt)*] [$(#[doc = $line:literal])*] $($field:tt)*}
+        $($rest:tt)*
+    ) => {
+        $crate::config_cfg! {
+            {$($head)*} {$($derived)*} {$($tail)*}
+            [$($done)* {$kind [$($cfg)*] [$($doc)* $(#[doc = $line])*] [] $($field)*}]
+            $($rest)*
+        }
+    };
+    (
+        {$($head:tt)*} {$($derived:tt)*} {$($tail:tt)*} [$($done:tt)*]
+        {$kind:ident [$($cfg:tt)*] [$($doc:tt)*] [#[cfg $($attr:tt)*] $($attrs:tt)*] $($field:tt)*}
+        $($rest:tt)*
+    ) => {
+        $crate::config_cfg! {
+            {$($head)*} {$($derived)*} {$($tail)*} [$($done)*]
+            {$kind [$($cfg)* #[cfg $($attr)*]] [$($doc)*] [$($attrs)*] $($field)*}
+            $($rest)*
+        }
+    };
+    (
+        {$($head:tt)*} {$($derived:tt)*} {$($tail:tt)*} [$($done:tt)*]
+        {$kind:ident [$($cfg:tt)*] [$($doc:tt)*] [#[doc $($attr:tt)*] $($attrs:tt)*] $($field:tt)*}
+        $($rest:tt)*
+    ) => {
+        $crate::config_cfg! {
+            {$($head)*} {$($derived)*} {$($tail)*} [$($done)*]
+            {$kind [$($cfg)*] [$($doc)* #[doc $($attr)*]] [$($attrs)*] $($field)*}
+            $($rest)*
+        }
+    };
+    (
+        {$($head:tt)*} {$($derived:tt)*} {$($tail:tt)*} [$($done:tt)*]
+        {$kind:ident [$($cfg:tt)*] [$($doc:tt)*] [#[$($attr:tt)*] $($attrs:tt)*] $($field:tt)*}
+        $($rest:tt)*
+    ) => {
+        $crate::config_cfg! {
+            {$($head)*} {$($derived)*} {$($tail)*} [$($done)*]
+            {$kind [$($cfg)*] [$($doc)*] [$($attrs)*] $($field)*}
+            $($rest)*
+        }
+    };
+}
+
+// Positional arguments take one name, which isn't option-like, and positions run from 0 without gaps.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! check_position {
+    ($name:ident [$($cli_name:literal),+]) => {};
+    ($name:ident [$cli_name:literal] ; $cli_position:literal) => {
+        const _: () = assert!(
+            !$crate::starts_with_dash($cli_name),
+            concat!("Positional argument `", stringify!($name), "` can't have a name starting with `-`.")
+        );
+        const _: () = assert!(
+            $crate::count_position(ALL_POSITIONS, $cli_position) == 1,
+            concat!("Positional argument `", stringify!($name), "` shares its position with another argument.")
+        );
+        const _: () = assert!(
+            $cli_position < $crate::count_positionals(ALL_POSITIONS),
+            concat!("Positional argument `", stringify!($name), "` leaves a gap before its position.")
+        );
+    };
+    ($name:ident [$($cli_name:literal),+] ; $cli_position:literal) => {
+        compile_error!(concat!("Positional argument `", stringify!($name), "` can only have one name."));
+    };
+}
@@ -0,0 +1,449 @@
+// Copyright (c) 2023 Zoe <zoe@zyoh.ca>
+
+use std::error::Error;
+use std::str::FromStr;
+
+use crate::Optional;
+
+#[derive(Debug, Clone)]
+pub struct ParseStyle {
+    pub prefixes: Vec<String>,
+    pub separators: Vec<char>,
+    pub case_sensitive_keys: bool,
+    pub case_sensitive_values: bool
+}
+
+impl Default for ParseStyle {
+    fn default() -> Self {
+        Self {
+            prefixes: vec![String::from("-")],
+            separators: vec!['='],
+            case_sensitive_keys: true,
+            case_sensitive_values: true
+        }
+    }
+}
+
+impl ParseStyle {
+    pub fn is_option(&self, arg: &str) -> bool {
+        self.prefixes.iter().any(|prefix| arg.starts_with(prefix.as_str()))
+    }
+
+    pub fn split_value<'a>(&self, arg: &'a str) -> Option<(&'a str, &'a str)> {
+        arg.split_once(|c| self.separators.contains(&c))
+    }
+
+    pub fn keys_match(&self, declared: &str, given: &str) -> bool {
+        if self.case_sensitive_keys {
+            declared == given
+        } else {
+            declared.to_lowercase() == given.to_lowercase()
+        }
+    }
+
+    // Spellings of `value` to try with `FromStr`, as given first.
+    // Values aren't declared anywhere, so case-insensitive matching is a heuristic: besides the given spelling
+    // it tries lowercase, UPPERCASE and Capitalized. Mixed-case values like `ReadWrite` only match when given
+    // in their exact spelling, or the type's `FromStr` has to ignore case itself.
+    pub fn value_spellings(&self, value: &str) -> Vec<String> {
+        let mut spellings = vec![value.to_string()];
+        if !self.case_sensitive_values {
+            let lower = value.to_lowercase();
+            let mut chars = lower.chars();
+            let capitalized = match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new()
+            };
+            spellings.extend([lower, value.to_uppercase(), capitalized]);
+        }
+        spellings
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    #[default]
+    Override,
+    KeepFirst,
+    Error,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    pub abbreviations: bool,
+    pub style: ParseStyle,
+    // Overrides the config's policy when set, arguments with their own policy keep it.
+    pub duplicates: Option<DuplicatePolicy>,
+    pub collect_errors: bool
+}
+
+// The `FromStr` error of a rejected value. Only `Display` is required of those, so the message is kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueError {
+    pub message: String
+}
+
+impl std::fmt::Display for ValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for ValueError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidValue {
+        field_name: String,
+        expected: String,
+        value: String,
+        source: ValueError,
+        index: usize
+    },
+    ExpectedPositional {
+        field_name: String,
+        found: String,
+        index: usize
+    },
+    AmbiguousOption {
+        key: String,
+        candidates: Vec<String>,
+        index: usize
+    },
+    DuplicateOption {
+        field_name: String,
+        key: String,
+        index: usize
+    },
+    MissingRequired {
+        field_name: String
+    },
+    MissingValue {
+        field_name: String,
+        key: String,
+        index: usize
+    },
+    UnknownOption {
+        key: String,
+        index: usize
+    },
+    Multiple(Vec<ParseError>),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::InvalidValue { field_name, expected, value, source, .. } => write!(f,
+                "Invalid value `{}` for argument `{}`, expected {}: {}",
+                value, field_name, expected, source
+            ),
+            ParseError::ExpectedPositional { field_name, found, .. } => write!(f,
+                "Expected required positional argument `{}`, found keyword argument `{}`.",
+                field_name, found
+            ),
+            ParseError::AmbiguousOption { key, candidates, .. } => write!(f,
+                "Ambiguous option `{}`, could be {}.",
+                key,
+                candidates.iter().map(|k| format!("`{}`", k)).collect::<Vec<String>>().join(", ")
+            ),
+            ParseError::DuplicateOption { field_name, key, .. } => write!(f,
+                "Argument `{}` was given more than once, again as `{}`.",
+                field_name, key
+            ),
+            ParseError::MissingRequired { field_name } => write!(f, "Missing required argument `{}`.", field_name),
+            ParseError::MissingValue { key, .. } => write!(f, "Option `{}` requires a value.", key),
+            ParseError::UnknownOption { key, .. } => write!(f, "Unknown option `{}`.", key),
+            ParseError::Multiple(errors) => {
+                write!(f, "Found {} error{}:", errors.len(), if errors.len() == 1 { "" } else { "s" })?;
+                for (i, error) in errors.iter().enumerate() {
+                    write!(f, "\n  {}. {}", i + 1, error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::InvalidValue { source, .. } => Some(source),
+            _ => None
+        }
+    }
+}
+
+impl ParseError {
+    pub fn index(&self) -> Option<usize> {
+        // Position of the offending token in the parsed arguments, the executable name is 0.
+        match self {
+            ParseError::InvalidValue { index, .. }
+            | ParseError::ExpectedPositional { index, .. }
+            | ParseError::AmbiguousOption { index, .. }
+            | ParseError::DuplicateOption { index, .. }
+            | ParseError::MissingValue { index, .. }
+            | ParseError::UnknownOption { index, .. } => Some(*index),
+            ParseError::MissingRequired { .. } | ParseError::Multiple(_) => None
+        }
+    }
+
+    pub fn render(&self, args: &[String], usage: &str) -> String {
+        let mut rendered = String::new();
+
+        match self {
+            ParseError::Multiple(errors) => {
+                for error in errors {
+                    rendered.push_str(error.render_marker(args).as_str());
+                }
+            },
+            error => rendered.push_str(error.render_marker(args).as_str())
+        }
+
+        rendered.push_str(format!("\n{}\n", usage).as_str());
+        rendered
+    }
+
+    fn render_marker(&self, args: &[String]) -> String {
+        let command_line = args.join(" ");
+
+        // Missing arguments are marked right after the command line.
+        let (start, length) = match self.index() {
+            Some(index) if index < args.len() => (
+                args[..index].iter().map(|arg| arg.chars().count() + 1).sum(),
+                args[index].chars().count().max(1)
+            ),
+            _ => (command_line.chars().count() + 1, 1)
+        };
+
+        format!("error: {}\n    {}\n    {}{}\n",
+            self,
+            command_line,
+            String::from(" ").repeat(start),
+            String::from("^").repeat(length)
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarning {
+    Deprecated {
+        field_name: String,
+        key: String,
+        note: String
+    },
+}
+
+impl std::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseWarning::Deprecated { key, note, .. } => write!(f, "`{}` is deprecated: {}", key, note)
+        }
+    }
+}
+
+// Whether `arg` is written as a number: an optional sign, digits, an optional decimal part and an optional exponent.
+// Unlike `f64`'s `FromStr` this leaves out `inf` and `NaN`, which are more likely misspelled options.
+#[doc(hidden)]
+pub fn is_number(arg: &str) -> bool {
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit());
+
+    let number = arg.strip_prefix(['-', '+']).unwrap_or(arg);
+    let (mantissa, exponent) = match number.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (number, None)
+    };
+    let mantissa = match mantissa.split_once('.') {
+        Some((whole, fraction)) => digits(whole) && digits(fraction),
+        None => digits(mantissa)
+    };
+
+    mantissa && exponent.is_none_or(|exponent| digits(exponent.strip_prefix(['-', '+']).unwrap_or(exponent)))
+}
+
+fn parse_str<T: FromStr>(value: &str, style: &ParseStyle) -> Result<T, ValueError>
+    where T::Err: std::fmt::Display
+{
+    let mut message = String::new();
+    for spelling in style.value_spellings(value) {
+        match spelling.parse::<T>() {
+            Ok(value) => return Ok(value),
+            // Report why the value as given failed, not one of the other spellings.
+            Err(error) => if message.is_empty() {
+                message = error.to_string();
+            }
+        }
+    }
+    Err(ValueError { message })
+}
+
+// Picks how a field's value is parsed from its type: `(&&&ValueParser::<T>(PhantomData)).parse_value(..)`
+// resolves to `ParseOsValue` for types built from the raw argument, then to `ParseOptionValue`
+// for `Option<T>` and to `ParseStrValue` otherwise.
+#[doc(hidden)]
+pub struct ValueParser<T>(pub std::marker::PhantomData<T>);
+
+#[doc(hidden)]
+pub trait ParseOsValue {
+    type Value;
+    fn parse_value(&self, value: &std::ffi::OsStr, style: &ParseStyle) -> Result<Self::Value, ValueError>;
+}
+
+impl ParseOsValue for &&ValueParser<std::path::PathBuf> {
+    type Value = std::path::PathBuf;
+    fn parse_value(&self, value: &std::ffi::OsStr, _style: &ParseStyle) -> Result<Self::Value, ValueError> {
+        Ok(std::path::PathBuf::from(value))
+    }
+}
+
+impl ParseOsValue for &&ValueParser<std::ffi::OsString> {
+    type Value = std::ffi::OsString;
+    fn parse_value(&self, value: &std::ffi::OsStr, _style: &ParseStyle) -> Result<Self::Value, ValueError> {
+        Ok(value.to_os_string())
+    }
+}
+
+impl ParseOsValue for &&ValueParser<Option<std::path::PathBuf>> {
+    type Value = Option<std::path::PathBuf>;
+    fn parse_value(&self, value: &std::ffi::OsStr, _style: &ParseStyle) -> Result<Self::Value, ValueError> {
+        Ok(Some(std::path::PathBuf::from(value)))
+    }
+}
+
+impl ParseOsValue for &&ValueParser<Option<std::ffi::OsString>> {
+    type Value = Option<std::ffi::OsString>;
+    fn parse_value(&self, value: &std::ffi::OsStr, _style: &ParseStyle) -> Result<Self::Value, ValueError> {
+        Ok(Some(value.to_os_string()))
+    }
+}
+
+impl ParseOsValue for &&ValueParser<Optional<std::path::PathBuf>> {
+    type Value = Optional<std::path::PathBuf>;
+    fn parse_value(&self, value: &std::ffi::OsStr, _style: &ParseStyle) -> Result<Self::Value, ValueError> {
+        Ok(Optional::Some(std::path::PathBuf::from(value)))
+    }
+}
+
+impl ParseOsValue for &&ValueParser<Optional<std::ffi::OsString>> {
+    type Value = Optional<std::ffi::OsString>;
+    fn parse_value(&self, value: &std::ffi::OsStr, _style: &ParseStyle) -> Result<Self::Value, ValueError> {
+        Ok(Optional::Some(value.to_os_string()))
+    }
+}
+
+#[doc(hidden)]
+pub trait ParseOptionValue {
+    type Value;
+    fn parse_value(&self, value: &std::ffi::OsStr, style: &ParseStyle) -> Result<Self::Value, ValueError>;
+}
+
+impl<T: FromStr> ParseOptionValue for &ValueParser<Option<T>> where T::Err: std::fmt::Display {
+    type Value = Option<T>;
+    fn parse_value(&self, value: &std::ffi::OsStr, style: &ParseStyle) -> Result<Self::Value, ValueError> {
+        ValueParser::<T>(std::marker::PhantomData).parse_value(value, style).map(Some)
+    }
+}
+
+#[doc(hidden)]
+pub trait ParseStrValue {
+    type Value;
+    fn parse_value(&self, value: &std::ffi::OsStr, style: &ParseStyle) -> Result<Self::Value, ValueError>;
+}
+
+impl<T: FromStr> ParseStrValue for ValueParser<T> where T::Err: std::fmt::Display {
+    type Value = T;
+    fn parse_value(&self, value: &std::ffi::OsStr, style: &ParseStyle) -> Result<Self::Value, ValueError> {
+        match value.to_str() {
+            Some(value) => parse_str(value, style),
+            None => Err(ValueError { message: String::from("not valid UTF-8") })
+        }
+    }
+}
+
+// Same dispatch for whether a field may be left out: `Option<T>` and `Optional<T>` may be.
+#[doc(hidden)]
+pub trait OptionalValue {
+    fn is_optional(&self) -> bool {
+        true
+    }
+}
+
+impl<T> OptionalValue for &&ValueParser<Option<T>> {}
+impl<T> OptionalValue for &&ValueParser<Optional<T>> {}
+
+#[doc(hidden)]
+pub trait RequiredValue {
+    fn is_optional(&self) -> bool {
+        false
+    }
+}
+
+impl<T> RequiredValue for ValueParser<T> {}
+
+// And for whether a bare key is enough: `bool` fields are flags, other fields need a value or a `missing_value`.
+#[doc(hidden)]
+pub trait FlagValue {
+    fn is_flag(&self) -> bool {
+        true
+    }
+}
+
+impl FlagValue for &&ValueParser<bool> {}
+impl FlagValue for &&ValueParser<Option<bool>> {}
+impl FlagValue for &&ValueParser<Optional<bool>> {}
+
+#[doc(hidden)]
+pub trait ValueTaking {
+    fn is_flag(&self) -> bool {
+        false
+    }
+}
+
+impl<T> ValueTaking for ValueParser<T> {}
+
+// Picks how a default is read from its expression: `(&&&DefaultParser::<T, _>(PhantomData, default)).default_value()`
+// resolves to `DisplayedDefault` for a value of the field type that can be shown in the help, then to
+// `TypedDefault` for other values of the field type and to `TextDefault` for text to parse like a value.
+#[doc(hidden)]
+pub struct DefaultParser<T, D>(pub std::marker::PhantomData<T>, pub D);
+
+#[doc(hidden)]
+pub enum DefaultValue<T> {
+    // The value and how the help shows it.
+    Value(T, Option<String>),
+    Text(String)
+}
+
+#[doc(hidden)]
+pub trait DisplayedDefault<T> {
+    fn default_value(&self) -> DefaultValue<T>;
+}
+
+impl<T: Clone + std::fmt::Display> DisplayedDefault<T> for &&DefaultParser<T, T> {
+    fn default_value(&self) -> DefaultValue<T> {
+        // An empty `Optional` shows as nothing, which isn't worth listing.
+        let shown = Some(self.1.to_string()).filter(|shown| !shown.is_empty());
+        DefaultValue::Value(self.1.clone(), shown)
+    }
+}
+
+#[doc(hidden)]
+pub trait TypedDefault<T> {
+    fn default_value(&self) -> DefaultValue<T>;
+}
+
+impl<T: Clone> TypedDefault<T> for &DefaultParser<T, T> {
+    fn default_value(&self) -> DefaultValue<T> {
+        DefaultValue::Value(self.1.clone(), None)
+    }
+}
+
+#[doc(hidden)]
+pub trait TextDefault<T> {
+    fn default_value(&self) -> DefaultValue<T>;
+}
+
+impl<T, D: std::fmt::Display> TextDefault<T> for DefaultParser<T, D> {
+    fn default_value(&self) -> DefaultValue<T> {
+        DefaultValue::Text(self.1.to_string())
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright (c) 2023 Zoe <zoe@zyoh.ca>
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use std::path::PathBuf;
 
@@ -13,6 +14,15 @@ mod tests {
         // [pub] struct <NAME> for "<EXECUTABLE_NAME>";
         // Example:
         pub struct Config for "application.exe";
+
+        // Optional text shown around the argument list in the help message.
+        @about "Renders the input file with a template.";
+        @long_about "The rendered output is written next to the input file unless an output path is given.";
+        @examples {
+            "notes.txt" => "Renders notes.txt to out.txt in the same directory.";
+            "notes.txt -t=letter -V" => "Uses the `letter` template and logs each step.";
+        }
+        @after_help "Report bugs at https://github.com/Zyoh/Rust-Argument-Handler.";
     
         //                        --- Optional ---               - Optional -  ---        Optional        ---
         // struct_field_name: type = default_value; ["cli_name", cli_position] "Description of the argument."
@@ -27,9 +37,19 @@ mod tests {
             "Enables verbose logging.";
         help: bool, ["-h", "--help"], 
             "Shows this help message.";
+
+        // Arguments after a section marker are listed under that heading in the help message.
+        @section "Advanced";
+        /// The template to use.
+        ///
+        /// Templates are looked up in the current directory first.
+        ///
+        /// Set `TEMPLATE_DIR` to look somewhere else.
         template: Optional<String> = "default_template_string", 
-            ["-t", "--template"], 
-            "The template to use.";
+            ["-t", "--template"] {
+                // Settings: long_help = "Shown with --help only.", hide_short_help
+                hide_short_help,
+            };
 
         // These are fields that are not exposed to the user / command line.
         @internal {
@@ -51,45 +71,1047 @@ mod tests {
         }
     }
 
-    #[test]
-    fn argument_info() {
-        // This should always work given correct configuration.
-        // TODO: Check if incorrect configuration unnoticed in compile-time is possible.
-        let args = config::Config::get_arguments().unwrap();
-        println!("{:#?}", args);
+    config_setup! {
+        in legacy;
+
+        #[derive(Debug)]
+        pub struct Legacy for "legacy.exe";
+
+        verbose: bool, ["-V", "--verbose"], "Enables verbose logging.";
+        version: bool, ["--version"], "Prints the version.";
+        // `--color` alone means `--color=always`, leaving it out means `never`.
+        // `--color=none` unsets it, so the terminal decides.
+        color: Optional<String> = "never", ["--color"], "When to use colors." {
+            missing_value = "always",
+            none_value = "none",
+        };
+        // `--template` was renamed to `--theme`, the old name keeps working but is no longer documented.
+        theme: Optional<String>, ["--theme"], "The theme to use." {
+            aliases = ["--style"],
+            hidden_aliases = ["--template"],
+        };
+        // Hidden arguments still parse, deprecated ones also produce a warning.
+        quiet: bool, ["-q", "--quiet"], "Disables logging." {
+            hidden,
+            deprecated = "logging is off unless --verbose is given",
+        };
+        // Left out of this build, so neither a field nor an argument.
+        #[cfg(any())]
+        trace: bool, ["--trace"], "Logs every step.";
+        // Its type is left out as well.
+        #[cfg(any())]
+        socket: Socket, ["--socket"], "Listens on a socket.";
+        // One field in two versions, only one of which is built.
+        #[cfg(unix)]
+        shell: PathBuf = "/bin/sh", ["--shell"], "The shell to run.";
+        #[cfg(not(unix))]
+        shell: String = "cmd.exe", ["--shell"], "The shell to run.";
     }
 
-    #[test]
-    fn help_message() {
-        let options = config::HelpOptions {
-            description_offset: 8, // (Default: 8) Offset of the description starting from the longest argument name.
-            description_max_length: 50, // (Default: 50) Maximum length of the description.
-            split_at: config::SplitAt::Space, // (Default: Space) When to split the description. Can be Any, Space, or None.
-            description_newline_extra_padding: 2, // (Default: 2) Extra padding for the description when it's on a new line.
-            indent_length: 4 // (Default: 4) Indentation of the arguments.
+    #[cfg(any())]
+    #[derive(Debug, Clone, Default)]
+    pub struct Socket;
+
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub enum Mode {
+        #[default]
+        Text,
+        Binary,
+    }
+
+    impl std::str::FromStr for Mode {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "Text" => Ok(Mode::Text),
+                "Binary" => Ok(Mode::Binary),
+                _ => Err(format!("Unknown mode `{}`.", s))
+            }
+        }
+    }
+
+    config_setup! {
+        in dos;
+
+        #[derive(Debug)]
+        pub struct Copy for "copy.exe";
+
+        source: PathBuf, ["source"; 0];
+        // Keys carry their own prefix, the parse style decides which tokens are options.
+        verbose: bool, ["/Verbose", "+v"], "Enables verbose logging.";
+        mode: Mode = "Text", ["/Mode"], "Text or Binary.";
+    }
+
+    config_setup! {
+        in offset;
+
+        #[derive(Debug)]
+        pub struct Offset for "offset.exe";
+
+        amount: i32, ["amount"; 0], "How far to move.";
+        // Accepts `-` as the usual name for stdin.
+        input: String = "stdin", ["input"; 1], "Where to read from." { allow_hyphen_values };
+        // A short option named like a negative number takes precedence over the value.
+        one: bool, ["-1"], "Moves by one line.";
+    }
+
+    // Optional fields are recognized through aliases too.
+    type Target = Option<String>;
+
+    config_setup! {
+        in deploy;
+
+        #[derive(Debug)]
+        pub struct Deploy for "deploy.exe";
+
+        // A repeated option keeps its first value unless the parse options say otherwise.
+        @duplicates DuplicatePolicy::KeepFirst;
+
+        tag: Option<String>, ["-t", "--tag"], "Tag to deploy.";
+        // Giving the target twice is always a mistake.
+        target: Target, ["--target"], "Where to deploy." { duplicates = DuplicatePolicy::Error };
+        port: Option<u16> = "8080", ["--port"], "Port of the deployed service.";
+        // Only read inside the crate.
+        #[allow(dead_code)]
+        pub(crate) manifest: std::option::Option<PathBuf>, ["--manifest"], "Manifest to deploy from.";
+    }
+
+    config_setup! {
+        in build;
+
+        #[derive(Debug)]
+        pub struct Build for "build.exe";
+
+        // Defaults of the field type are used as they are, shown in the help if they implement Display.
+        jobs: u8 = 4, ["-j", "--jobs"], "Parallel jobs.";
+        log: PathBuf = PathBuf::from("build.log"), ["--log"], "Where to write the log.";
+        out: PathBuf = PathBuf::from("target").join("out") => "target/out", ["--out"], "Output directory.";
+        profile: Option<String> = Some("dev".to_string()) => "dev", ["--profile"], "Build profile.";
+        summary: PathBuf, ["--summary"], "Where to write the summary.";
+        report: PathBuf, ["--report"], "Where to write the report.";
+
+        // Listed before the default it reads, they run in dependency order.
+        @derived {
+            summary(report) = report.with_extension("md");
+            report(out, profile) = out.join(format!("{}.txt", profile.as_deref().unwrap_or("dev")));
+        }
+    }
+
+    // A section declared again continues the first one.
+    config_setup! {
+        in layout;
+
+        #[derive(Debug)]
+        pub struct Layout for "layout.exe";
+
+        verbose: bool, ["-V", "--verbose"], "Enables verbose logging.";
+
+        @section "Input";
+        input: PathBuf, ["input"; 0], "File to read.";
+        encoding: String = "utf-8", ["--encoding"], "Encoding of the input.";
+
+        @section "Output";
+        output: PathBuf = "out.txt", ["-o", "--output"], "File to write.";
+
+        @section "Input";
+        strict: bool, ["--strict"], "Rejects malformed input.";
+    }
+
+    // Options without a default are required like positionals.
+    config_setup! {
+        in greet;
+
+        #[derive(Debug)]
+        pub struct Greet for "greet.exe";
+
+        name: String, ["-n", "--name"], "Who to greet.";
+        loud: bool, ["--loud"], "Greets in capitals.";
+    }
+
+    // More fields than the default recursion limit of 128.
+    config_setup! {
+        in wide;
+
+        #[derive(Debug)]
+        pub struct Wide for "wide.exe";
+
+        f000: bool, ["--f000"]; f001: bool, ["--f001"]; f002: bool, ["--f002"]; f003: bool, ["--f003"]; f004: bool, ["--f004"]; f005: bool, ["--f005"]; f006: bool, ["--f006"]; f007: bool, ["--f007"]; f008: bool, ["--f008"]; f009: bool, ["--f009"];
+        f010: bool, ["--f010"]; f011: bool, ["--f011"]; f012: bool, ["--f012"]; f013: bool, ["--f013"]; f014: bool, ["--f014"]; f015: bool, ["--f015"]; f016: bool, ["--f016"]; f017: bool, ["--f017"]; f018: bool, ["--f018"]; f019: bool, ["--f019"];
+        f020: bool, ["--f020"]; f021: bool, ["--f021"]; f022: bool, ["--f022"]; f023: bool, ["--f023"]; f024: bool, ["--f024"]; f025: bool, ["--f025"]; f026: bool, ["--f026"]; f027: bool, ["--f027"]; f028: bool, ["--f028"]; f029: bool, ["--f029"];
+        f030: bool, ["--f030"]; f031: bool, ["--f031"]; f032: bool, ["--f032"]; f033: bool, ["--f033"]; f034: bool, ["--f034"]; f035: bool, ["--f035"]; f036: bool, ["--f036"]; f037: bool, ["--f037"]; f038: bool, ["--f038"]; f039: bool, ["--f039"];
+        f040: bool, ["--f040"]; f041: bool, ["--f041"]; f042: bool, ["--f042"]; f043: bool, ["--f043"]; f044: bool, ["--f044"]; f045: bool, ["--f045"]; f046: bool, ["--f046"]; f047: bool, ["--f047"]; f048: bool, ["--f048"]; f049: bool, ["--f049"];
+        f050: bool, ["--f050"]; f051: bool, ["--f051"]; f052: bool, ["--f052"]; f053: bool, ["--f053"]; f054: bool, ["--f054"]; f055: bool, ["--f055"]; f056: bool, ["--f056"]; f057: bool, ["--f057"]; f058: bool, ["--f058"]; f059: bool, ["--f059"];
+        f060: bool, ["--f060"]; f061: bool, ["--f061"]; f062: bool, ["--f062"]; f063: bool, ["--f063"]; f064: bool, ["--f064"]; f065: bool, ["--f065"]; f066: bool, ["--f066"]; f067: bool, ["--f067"]; f068: bool, ["--f068"]; f069: bool, ["--f069"];
+        f070: bool, ["--f070"]; f071: bool, ["--f071"]; f072: bool, ["--f072"]; f073: bool, ["--f073"]; f074: bool, ["--f074"]; f075: bool, ["--f075"]; f076: bool, ["--f076"]; f077: bool, ["--f077"]; f078: bool, ["--f078"]; f079: bool, ["--f079"];
+        f080: bool, ["--f080"]; f081: bool, ["--f081"]; f082: bool, ["--f082"]; f083: bool, ["--f083"]; f084: bool, ["--f084"]; f085: bool, ["--f085"]; f086: bool, ["--f086"]; f087: bool, ["--f087"]; f088: bool, ["--f088"]; f089: bool, ["--f089"];
+        f090: bool, ["--f090"]; f091: bool, ["--f091"]; f092: bool, ["--f092"]; f093: bool, ["--f093"]; f094: bool, ["--f094"]; f095: bool, ["--f095"]; f096: bool, ["--f096"]; f097: bool, ["--f097"]; f098: bool, ["--f098"]; f099: bool, ["--f099"];
+        f100: bool, ["--f100"]; f101: bool, ["--f101"]; f102: bool, ["--f102"]; f103: bool, ["--f103"]; f104: bool, ["--f104"]; f105: bool, ["--f105"]; f106: bool, ["--f106"]; f107: bool, ["--f107"]; f108: bool, ["--f108"]; f109: bool, ["--f109"];
+        f110: bool, ["--f110"]; f111: bool, ["--f111"]; f112: bool, ["--f112"]; f113: bool, ["--f113"]; f114: bool, ["--f114"]; f115: bool, ["--f115"]; f116: bool, ["--f116"]; f117: bool, ["--f117"]; f118: bool, ["--f118"]; f119: bool, ["--f119"];
+        f120: bool, ["--f120"]; f121: bool, ["--f121"]; f122: bool, ["--f122"]; f123: bool, ["--f123"]; f124: bool, ["--f124"]; f125: bool, ["--f125"]; f126: bool, ["--f126"]; f127: bool, ["--f127"]; f128: bool, ["--f128"]; f129: bool, ["--f129"];
+        f130: bool, ["--f130"]; f131: bool, ["--f131"]; f132: bool, ["--f132"]; f133: bool, ["--f133"]; f134: bool, ["--f134"]; f135: bool, ["--f135"]; f136: bool, ["--f136"]; f137: bool, ["--f137"]; f138: bool, ["--f138"]; f139: bool, ["--f139"];
+    }
+
+    // The default is only checked once the arguments are built.
+    config_setup! {
+        in broken;
+
+        #[derive(Debug)]
+        pub struct Broken for "broken.exe";
+
+        retries: u8 = "many", ["-r"], "How often to retry.";
+    }
+
+    // Like defaults, aliases are only checked once the arguments are built.
+    config_setup! {
+        in clash;
+
+        #[derive(Debug)]
+        pub struct Clash for "clash.exe";
+
+        verbose: bool, ["-v", "--verbose"], "Enables verbose logging.";
+        version: bool, ["--version"], "Prints the version." { hidden_aliases = ["-v"] };
+    }
+
+    // Run for the `config_setup!` configs here and for the derived ones in `derived`.
+    macro_rules! test_suite {
+        () => {
+        fn args(args: &[&str]) -> Vec<String> {
+            args.iter().map(|arg| arg.to_string()).collect()
+        }
+
+        #[test]
+        fn argument_info() {
+            // This should always work given correct configuration.
+            // TODO: Check if incorrect configuration unnoticed in compile-time is possible.
+            let args = config::Config::get_arguments().unwrap();
+            println!("{:#?}", args);
+        }
+
+        #[test]
+        fn help_message() {
+            let options = config::HelpOptions {
+                description_offset: 8, // (Default: 8) Offset of the description starting from the longest argument name.
+                description_max_length: 50, // (Default: 50) Maximum length of the description.
+                split_at: config::SplitAt::Space, // (Default: Space) When to split the description. Can be Any, Space, or None.
+                description_newline_extra_padding: 2, // (Default: 2) Extra padding for the description when it's on a new line.
+                indent_length: 4, // (Default: 4) Indentation of the arguments.
+                separate_positionals: true, // (Default: true) List positional arguments apart from the options.
+                positionals_heading: "Arguments".to_string(), // (Default: "Arguments") Heading of positional arguments without a section.
+                options_heading: "Options".to_string(), // (Default: "Options") Heading of options without a section.
+                order: config::ArgumentOrder::Declaration, // (Default: Declaration) Listing order. Can be Declaration, Alphabetical, or RequiredFirst.
+                detail: config::HelpDetail::Long, // (Default: Long) Short leaves out long descriptions and arguments hidden from short help.
+            };
+            config::Config::show_help(Some(options)).unwrap();
+        }
+
+        #[test]
+        fn argument_order() {
+            let field_names = |order| -> Vec<String> {
+                config::Config::arguments(order).unwrap()
+                    .into_iter()
+                    .map(|argument| argument.field_name)
+                    .collect()
+            };
+
+            assert_eq!(
+                field_names(config::ArgumentOrder::Declaration),
+                ["input_file", "output_file", "verbose", "help", "template"]
+            );
+            assert_eq!(
+                field_names(config::ArgumentOrder::Alphabetical),
+                ["help", "input_file", "output_file", "template", "verbose"]
+            );
+            assert_eq!(
+                field_names(config::ArgumentOrder::RequiredFirst),
+                ["input_file", "output_file", "verbose", "help", "template"]
+            );
+
+            // Flags are never required, so a required positional comes first even when declared after one.
+            let arguments = layout::Layout::arguments(config::ArgumentOrder::RequiredFirst).unwrap();
+            assert_eq!(arguments[0].field_name, "input");
+            assert!(arguments.iter().filter(|argument| argument.field_name != "input").all(|argument| argument.optional));
+        }
+
+        #[test]
+        fn help_text() {
+            let help = config::Config::help(None).unwrap();
+
+            assert!(help.starts_with("Renders the input file with a template.\n\nThe rendered output"));
+            assert!(help.contains("\nUsage: application.exe <input_file> [output]"));
+            assert!(help.contains(
+                "\n    application.exe notes.txt -t=letter -V\n        Uses the `letter` template and logs each step.\n"
+            ));
+            assert!(help.ends_with("\nReport bugs at https://github.com/Zyoh/Rust-Argument-Handler.\n"));
+
+            let usage = help.find("Usage:").unwrap();
+            let examples = help.find("Examples:").unwrap();
+            assert!(usage < help.find("Options:").unwrap());
+            assert!(help.find("Advanced:").unwrap() < examples);
+        }
+
+        #[test]
+        fn help_detail() {
+            let help = |detail| config::Config::help(Some(config::HelpOptions {
+                detail,
+                ..Default::default()
+            })).unwrap();
+
+            let short = help(config::HelpDetail::Short);
+            assert!(!short.contains("The rendered output"));
+            assert!(!short.contains("--template"));
+
+            let long = help(config::HelpDetail::Long);
+            assert!(long.contains("The rendered output"));
+            assert!(long.contains(
+                "-t, --template        The template to use.\n                            Templates are looked up"
+            ));
+            // Paragraphs of the long help are kept apart.
+            assert!(long.contains(
+                "current directory first.\n\n                            Set `TEMPLATE_DIR` to look somewhere else."
+            ));
+
+            // The first doc comment line is the description, the rest the long help.
+            let template = config::Config::get_arguments().unwrap().pop().unwrap();
+            assert_eq!(template.description, "The template to use.");
+            assert_eq!(
+                template.long_description,
+                "Templates are looked up in the current directory first.\n\nSet `TEMPLATE_DIR` to look somewhere else."
+            );
+
+            let requested = |args: Vec<String>| config::Config::requested_help(&args, "help").unwrap();
+            assert_eq!(requested(args(&["app", "in.txt"])), None);
+            assert_eq!(requested(args(&["app", "-h"])), Some(config::HelpDetail::Short));
+            assert_eq!(requested(args(&["app", "--help"])), Some(config::HelpDetail::Long));
+        }
+
+        #[test]
+        fn help_sections() {
+            let help = config::Config::help(None).unwrap();
+
+            let arguments = help.find("\nArguments:\n").unwrap();
+            let options = help.find("\nOptions:\n").unwrap();
+            let advanced = help.find("\nAdvanced:\n").unwrap();
+            assert!(arguments < options && options < advanced);
+
+            // Each argument is listed once, under its own heading.
+            assert!(help[arguments..options].contains("input_file"));
+            assert!(help[options..advanced].contains("-V, --verbose"));
+            assert!(!help[options..advanced].contains("--template"));
+            assert!(help[advanced..].contains("-t, --template"));
+        }
+
+        #[test]
+        fn repeated_sections() {
+            assert_eq!(layout::Layout::sections(), ["Input", "Output"]);
+
+            let help = layout::Layout::help(None).unwrap();
+            assert_eq!(help.matches("\nInput:\n").count(), 1);
+
+            let input = help.find("\nInput:\n").unwrap();
+            let output = help.find("\nOutput:\n").unwrap();
+            assert!(input < output);
+            // Arguments without a section stay under the default headings wherever they are declared.
+            assert!(help[..input].contains("-V, --verbose"));
+            assert!(!help[input..].contains("--verbose"));
+            assert!(help[input..output].contains("--encoding"));
+            assert!(help[input..output].contains("--strict"));
+            assert!(help[output..].contains("-o, --output"));
+        }
+
+        #[test]
+        fn parsing() {
+            let mut args = [
+                "appname.exec",
+                "/dev/null/input_file", // This is a required positional argument.
+                // "/dev/null/output_file", // This is an optional positional argument.
+                "-V", // This is a flag.
+                "-h", // This is a flag.
+                // "--template", "template_string" // This is an optional argument.
+            ];
+            let args: Vec<String> = args.iter_mut().map(|arg| arg.to_string()).collect();
+
+            let config = config::Config::parse_custom(args).unwrap();
+            println!("{:#?}", config);
+
+            assert_eq!(config.input_file, PathBuf::from("/dev/null/input_file"));
+            assert_eq!(config.output_file.to_option(), None);
+            assert!(config.verbose);
+            assert!(config.help);
+            assert_eq!(config.template.to_option(), Some("default_template_string".to_string()));
+        }
+
+        #[test]
+        fn hidden_and_deprecated() {
+            let help = legacy::Legacy::help(None).unwrap();
+            assert!(help.contains("--verbose"));
+            assert!(!help.contains("--quiet"));
+            // Fields left out by `#[cfg]` are no arguments.
+            assert!(!help.contains("--trace"));
+            let options = legacy::ParseOptions { collect_errors: true, ..Default::default() };
+            let error = legacy::Legacy::parse_custom_with(args(&["legacy.exe", "--trace"]), Some(options)).unwrap_err();
+            assert_eq!(error.to_string(), "Found 1 error:\n  1. Unknown option `--trace`.");
+            assert!(!help.contains("--socket"));
+
+            // Of a field declared per platform, the one built is the argument.
+            assert!(help.contains("--shell"));
+            let legacy = legacy::Legacy::parse_custom(args(&["legacy.exe"])).unwrap();
+            #[cfg(unix)]
+            assert_eq!(legacy.shell, PathBuf::from("/bin/sh"));
+            #[cfg(not(unix))]
+            assert_eq!(legacy.shell, "cmd.exe");
+            let legacy = legacy::Legacy::parse_custom(args(&["legacy.exe", "--shell=zsh"])).unwrap();
+            #[cfg(unix)]
+            assert_eq!(legacy.shell, PathBuf::from("zsh"));
+            #[cfg(not(unix))]
+            assert_eq!(legacy.shell, "zsh");
+
+            let (legacy, warnings) = legacy::Legacy::parse_custom_with_warnings(args(&["legacy.exe", "-q"])).unwrap();
+            assert!(legacy.quiet);
+            assert_eq!(warnings, vec![legacy::ParseWarning::Deprecated {
+                field_name: "quiet".to_string(),
+                key: "-q".to_string(),
+                note: "logging is off unless --verbose is given".to_string()
+            }]);
+            assert_eq!(warnings[0].to_string(), "`-q` is deprecated: logging is off unless --verbose is given");
+            // Left to the caller, `parse_custom` doesn't print them.
+            assert!(legacy::Legacy::parse_custom(args(&["legacy.exe", "-q"])).unwrap().quiet);
+
+            let (_, warnings) = legacy::Legacy::parse_custom_with_warnings(args(&["legacy.exe", "-V"])).unwrap();
+            assert!(warnings.is_empty());
+        }
+
+        #[test]
+        fn aliases() {
+            let help = legacy::Legacy::help(None).unwrap();
+            assert!(help.contains("--theme, --style"));
+            assert!(!help.contains("--template"));
+
+            for key in ["--theme", "--style", "--template"] {
+                let legacy = legacy::Legacy::parse_custom(args(&["legacy.exe", &format!("{}=dark", key)])).unwrap();
+                assert_eq!(legacy.theme.to_option(), Some("dark".to_string()));
+            }
+        }
+
+        #[test]
+        fn abbreviations() {
+            let options = legacy::ParseOptions { abbreviations: true, ..Default::default() };
+
+            let (legacy, _) = legacy::Legacy::parse_custom_with(args(&["legacy.exe", "--verb"]), Some(options.clone())).unwrap();
+            assert!(legacy.verbose);
+            let (legacy, _) = legacy::Legacy::parse_custom_with(args(&["legacy.exe", "--th=dark"]), Some(options.clone())).unwrap();
+            assert_eq!(legacy.theme.to_option(), Some("dark".to_string()));
+
+            // Hidden arguments are left out like in the help, only their full name works.
+            let (legacy, warnings) = legacy::Legacy::parse_custom_with(args(&["legacy.exe", "--qui"]), Some(options.clone())).unwrap();
+            assert!(!legacy.quiet);
+            assert!(warnings.is_empty());
+
+            // Abbreviations are opt-in.
+            let (legacy, _) = legacy::Legacy::parse_custom_with(args(&["legacy.exe", "--verb"]), None).unwrap();
+            assert!(!legacy.verbose);
+
+            let error = legacy::Legacy::parse_custom_with(args(&["legacy.exe", "--ver"]), Some(options)).unwrap_err();
+            assert_eq!(error.to_string(), "Ambiguous option `--ver`, could be `--verbose`, `--version`.");
+        }
+
+        #[test]
+        fn parse_style() {
+            let options = dos::ParseOptions {
+                style: dos::ParseStyle {
+                    prefixes: vec!["/".to_string(), "+".to_string()], // (Default: ["-"]) Tokens starting with these are options.
+                    separators: vec![':', '='], // (Default: ['=']) Characters between an option and its value.
+                    case_sensitive_keys: false, // (Default: true)
+                    case_sensitive_values: false, // (Default: true)
+                },
+                ..Default::default()
+            };
+            let (copy, _) = dos::Copy::parse_custom_with(args(&["copy.exe", "a.txt", "/VERBOSE", "/mode:binary"]), Some(options.clone())).unwrap();
+            assert_eq!(copy.source, PathBuf::from("a.txt"));
+            assert!(copy.verbose);
+            assert_eq!(copy.mode, Mode::Binary);
+
+            let (copy, _) = dos::Copy::parse_custom_with(args(&["copy.exe", "a.txt", "+v", "/Mode=Text"]), Some(options)).unwrap();
+            assert!(copy.verbose);
+            assert_eq!(copy.mode, Mode::Text);
+
+            // The default style only knows `-` options.
+            assert_eq!(dos::Copy::parse_custom(args(&["copy.exe", "a.txt", "/Mode=Binary"])).unwrap().mode, Mode::Text);
+        }
+
+        #[test]
+        fn missing_value() {
+            let legacy = legacy::Legacy::parse_custom(args(&["legacy.exe"])).unwrap();
+            assert_eq!(legacy.color.to_option(), Some("never".to_string()));
+            let legacy = legacy::Legacy::parse_custom(args(&["legacy.exe", "--color"])).unwrap();
+            assert_eq!(legacy.color.to_option(), Some("always".to_string()));
+            let legacy = legacy::Legacy::parse_custom(args(&["legacy.exe", "--color=auto"])).unwrap();
+            assert_eq!(legacy.color.to_option(), Some("auto".to_string()));
+            let legacy = legacy::Legacy::parse_custom(args(&["legacy.exe", "--color=none"])).unwrap();
+            assert_eq!(legacy.color.to_option(), None);
+            let legacy = legacy::Legacy::parse_custom(args(&["legacy.exe", "--color=None"])).unwrap();
+            assert_eq!(legacy.color.to_option(), Some("None".to_string()));
+
+            // Without a missing value, only flags may be given as a bare key.
+            let error = deploy::Deploy::parse_custom(args(&["deploy.exe", "--port"])).unwrap_err();
+            assert_eq!(error.downcast_ref::<deploy::ParseError>(), Some(&deploy::ParseError::MissingValue {
+                field_name: "port".to_string(),
+                key: "--port".to_string(),
+                index: 1
+            }));
+            assert_eq!(error.to_string(), "Option `--port` requires a value.");
+        }
+
+        #[test]
+        fn optional_api() {
+            use legacy::Optional;
+
+            let some: Optional<u8> = Some(3).into();
+            let none: Optional<u8> = None.into();
+
+            assert!(some.is_some() && none.is_none());
+            assert_eq!(Option::from(some), Some(3));
+            assert_eq!(some.as_ref().map(|value| value * 2), Optional::Some(6));
+            assert_eq!(none.unwrap_or(7), 7);
+            assert_eq!(some.iter().chain(&none).collect::<Vec<_>>(), vec![&3]);
+            assert_eq!(format!("[{}] [{}]", some, none), "[3] []");
+            assert_eq!("None".parse::<Optional<String>>(), Ok(Optional::Some("None".to_string())));
+        }
+
+        #[test]
+        fn hyphen_values() {
+            let parsed = offset::Offset::parse_custom(args(&["offset.exe", "-5", "-"])).unwrap();
+            assert_eq!(parsed.amount, -5);
+            assert_eq!(parsed.input, "-");
+            assert!(!parsed.one);
+
+            let parsed = offset::Offset::parse_custom(args(&["offset.exe", "-2.5e3"]));
+            assert!(parsed.is_err());
+
+            let error = offset::Offset::parse_custom(args(&["offset.exe", "-1"])).unwrap_err();
+            assert_eq!(error.to_string(), "Expected required positional argument `amount`, found keyword argument `-1`.");
+
+            // Only plain numbers, not everything `f64` parses.
+            let error = offset::Offset::parse_custom(args(&["offset.exe", "-inf"])).unwrap_err();
+            assert_eq!(error.to_string(), "Expected required positional argument `amount`, found keyword argument `-inf`.");
+        }
+
+        #[test]
+        fn duplicates() {
+            let tags = args(&["deploy.exe", "--tag=a", "-t=b"]);
+
+            // The config's policy applies unless the parse options set one.
+            let deploy = deploy::Deploy::parse_custom(tags.clone()).unwrap();
+            assert_eq!(deploy.tag, Some("a".to_string()));
+
+            let options = deploy::ParseOptions { duplicates: Some(deploy::DuplicatePolicy::Override), ..Default::default() };
+            let (deploy, _) = deploy::Deploy::parse_custom_with(tags.clone(), Some(options)).unwrap();
+            assert_eq!(deploy.tag, Some("b".to_string()));
+
+            let options = deploy::ParseOptions { duplicates: Some(deploy::DuplicatePolicy::KeepFirst), ..Default::default() };
+            let (deploy, _) = deploy::Deploy::parse_custom_with(tags.clone(), Some(options)).unwrap();
+            assert_eq!(deploy.tag, Some("a".to_string()));
+
+            let options = deploy::ParseOptions { duplicates: Some(deploy::DuplicatePolicy::Error), ..Default::default() };
+            let error = deploy::Deploy::parse_custom_with(tags, Some(options)).unwrap_err();
+            assert_eq!(error.downcast_ref::<deploy::ParseError>(), Some(&deploy::ParseError::DuplicateOption {
+                field_name: "tag".to_string(),
+                key: "-t".to_string(),
+                index: 2
+            }));
+
+            // The per-argument policy wins over the parse options and the config.
+            let options = deploy::ParseOptions { duplicates: Some(deploy::DuplicatePolicy::Override), ..Default::default() };
+            let error = deploy::Deploy::parse_custom_with(args(&["deploy.exe", "--target=prod", "--target=staging"]), Some(options)).unwrap_err();
+            assert_eq!(error.to_string(), "Argument `target` was given more than once, again as `--target`.");
+        }
+
+        #[test]
+        fn option_fields() {
+            let deploy = deploy::Deploy::parse_custom(args(&["deploy.exe"])).unwrap();
+            assert_eq!(deploy.tag, None);
+            assert_eq!(deploy.target, None);
+            assert_eq!(deploy.port, Some(8080));
+
+            let deploy = deploy::Deploy::parse_custom(args(&["deploy.exe", "--target=prod", "--port=9000"])).unwrap();
+            assert_eq!(deploy.target, Some("prod".to_string()));
+            assert_eq!(deploy.port, Some(9000));
+
+            let arguments = deploy::Deploy::get_arguments().unwrap();
+            assert!(arguments.iter().all(|argument| argument.optional));
+        }
+
+        #[test]
+        fn collect_errors() {
+            let invalid = args(&["offset.exe", "ten", "-", "--fast", "-1=2"]);
+            let options = offset::ParseOptions { collect_errors: true, ..Default::default() };
+
+            // By default the first error is returned.
+            let error = offset::Offset::parse_custom(invalid.clone()).unwrap_err();
+            assert_eq!(error.to_string(), "Invalid value `ten` for argument `amount`, expected i32: invalid digit found in string");
+
+            let error = offset::Offset::parse_custom_with(invalid, Some(options.clone())).unwrap_err();
+            assert_eq!(error.downcast_ref::<offset::ParseError>(), Some(&offset::ParseError::Multiple(vec![
+                offset::ParseError::InvalidValue {
+                    field_name: "amount".to_string(),
+                    expected: "i32".to_string(),
+                    value: "ten".to_string(),
+                    source: offset::ValueError { message: "invalid digit found in string".to_string() },
+                    index: 1
+                },
+                offset::ParseError::InvalidValue {
+                    field_name: "one".to_string(),
+                    expected: "bool".to_string(),
+                    value: "2".to_string(),
+                    source: offset::ValueError { message: "provided string was not `true` or `false`".to_string() },
+                    index: 4
+                },
+                offset::ParseError::UnknownOption { key: "--fast".to_string(), index: 3 },
+            ])));
+            assert_eq!(error.to_string(), "Found 3 errors:\n  \
+                1. Invalid value `ten` for argument `amount`, expected i32: invalid digit found in string\n  \
+                2. Invalid value `2` for argument `one`, expected bool: provided string was not `true` or `false`\n  \
+                3. Unknown option `--fast`.");
+
+            let error = offset::Offset::parse_custom_with(args(&["offset.exe"]), Some(options)).unwrap_err();
+            assert_eq!(error.to_string(), "Found 1 error:\n  1. Missing required argument `amount`.");
+        }
+
+        #[test]
+        fn required_options() {
+            let greet = greet::Greet::parse_custom(args(&["greet.exe", "--name=Ada"])).unwrap();
+            assert_eq!(greet.name, "Ada");
+            assert!(greet::Greet::usage(None).unwrap().contains("greet.exe <-n, --name> [--loud]"));
+
+            let error = greet::Greet::parse_custom(args(&["greet.exe", "--loud"])).unwrap_err();
+            assert_eq!(error.to_string(), "Missing required argument `name`.");
+
+            let options = config::ParseOptions { collect_errors: true, ..Default::default() };
+            let error = greet::Greet::parse_custom_with(args(&["greet.exe", "--loud", "--fast"]), Some(options)).unwrap_err();
+            assert_eq!(error.to_string(), "Found 2 errors:\n  1. Missing required argument `name`.\n  2. Unknown option `--fast`.");
+        }
+
+        #[test]
+        fn render_errors() {
+            let invalid = args(&["offset.exe", "ten", "-", "--fast"]);
+            let options = offset::ParseOptions { collect_errors: true, ..Default::default() };
+            let error = offset::Offset::parse_custom_with(invalid.clone(), Some(options)).unwrap_err();
+
+            assert_eq!(offset::Offset::render_error(error.as_ref(), &invalid), "\
+error: Invalid value `ten` for argument `amount`, expected i32: invalid digit found in string
+    offset.exe ten - --fast
+               ^^^
+error: Unknown option `--fast`.
+    offset.exe ten - --fast
+                     ^^^^^^
+
+Usage: offset.exe <amount> [input] [-1]
+");
+
+            let missing = args(&["offset.exe"]);
+            let error = offset::Offset::parse_custom(missing.clone()).unwrap_err();
+            assert_eq!(offset::Offset::render_error(error.as_ref(), &missing), "\
+error: Missing required argument `amount`.
+    offset.exe
+               ^
+
+Usage: offset.exe <amount> [input] [-1]
+");
+        }
+
+        #[test]
+        fn value_error_source() {
+            let error = deploy::Deploy::parse_custom(args(&["deploy.exe", "--port=70000"])).unwrap_err();
+
+            assert_eq!(
+                error.to_string(),
+                "Invalid value `70000` for argument `port`, expected Option<u16>: number too large to fit in target type"
+            );
+            assert_eq!(error.source().unwrap().to_string(), "number too large to fit in target type");
+        }
+
+        #[test]
+        fn typed_defaults() {
+            let build = build::Build::parse_custom(args(&["build.exe"])).unwrap();
+            assert_eq!(build.jobs, 4);
+            assert_eq!(build.log, PathBuf::from("build.log"));
+            assert_eq!(build.out, PathBuf::from("target").join("out"));
+            assert_eq!(build.profile, Some("dev".to_string()));
+
+            let build = build::Build::parse_custom(args(&["build.exe", "-j=8", "--profile=release"])).unwrap();
+            assert_eq!(build.jobs, 8);
+            assert_eq!(build.profile, Some("release".to_string()));
+
+            let help = build::Build::help(None).unwrap();
+            assert!(help.contains("Parallel jobs. [default: 4]\n"));
+            assert!(help.contains("Where to write the log.\n"));
+            assert!(help.contains("Output directory. [default: target/out]\n"));
+            assert!(help.contains("Build profile. [default: dev]\n"));
+        }
+
+        #[test]
+        fn derived_defaults() {
+            let build = build::Build::parse_custom(args(&["build.exe"])).unwrap();
+            assert_eq!(build.report, PathBuf::from("target").join("out").join("dev.txt"));
+            assert_eq!(build.summary, PathBuf::from("target").join("out").join("dev.md"));
+
+            let build = build::Build::parse_custom(args(&["build.exe", "--out=dist", "--profile=release"])).unwrap();
+            assert_eq!(build.report, PathBuf::from("dist").join("release.txt"));
+            assert_eq!(build.summary, PathBuf::from("dist").join("release.md"));
+
+            // Given fields keep their value and are what later defaults read.
+            let build = build::Build::parse_custom(args(&["build.exe", "--report=report.txt", "--summary=notes.txt"])).unwrap();
+            assert_eq!(build.report, PathBuf::from("report.txt"));
+            assert_eq!(build.summary, PathBuf::from("notes.txt"));
+
+            let build = build::Build::parse_custom(args(&["build.exe", "--report=report.txt"])).unwrap();
+            assert_eq!(build.summary, PathBuf::from("report.md"));
+        }
+
+        #[test]
+        fn invalid_default() {
+            let error = broken::Broken::parse_custom(args(&["broken.exe"])).unwrap_err();
+
+            assert_eq!(
+                error.to_string(),
+                "Invalid default `many` for argument `retries`: invalid digit found in string"
+            );
+        }
+
+        #[test]
+        fn alias_collision() {
+            let error = clash::Clash::get_arguments().unwrap_err();
+            assert_eq!(error.to_string(), "Argument `version` uses the name `-v` of argument `verbose`.");
+
+            assert!(clash::Clash::parse_custom(args(&["clash.exe", "-v"])).is_err());
+        }
+
+        #[test]
+        #[cfg(unix)]
+        fn non_utf8_arguments() {
+            use std::ffi::OsString;
+            use std::os::unix::ffi::OsStringExt;
+
+            // `fo` followed by a byte that is never valid UTF-8.
+            let raw = || OsString::from_vec(vec![0x66, 0x6f, 0xff]);
+
+            let args = vec![OsString::from("copy.exe"), raw()];
+            let copy = dos::Copy::parse_custom_os(args).unwrap();
+            assert_eq!(copy.source.into_os_string(), raw());
+
+            let mut manifest = OsString::from("--manifest=");
+            manifest.push(raw());
+            let args = vec![OsString::from("deploy.exe"), manifest];
+            let deploy = deploy::Deploy::parse_custom_os(args).unwrap();
+            assert_eq!(deploy.manifest.map(PathBuf::into_os_string), Some(raw()));
+
+            let mut tag = OsString::from("--tag=");
+            tag.push(raw());
+            let args = vec![OsString::from("deploy.exe"), tag];
+            let error = deploy::Deploy::parse_custom_os(args).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "Invalid value `fo\u{FFFD}` for argument `tag`, expected Option<String>: not valid UTF-8"
+            );
+        }
+
+        #[test]
+        fn shared_types() {
+            fn keys<C: ArgumentConfig>() -> Vec<String> {
+                C::arguments(ArgumentOrder::Alphabetical).unwrap()
+                    .into_iter()
+                    .flat_map(|argument| argument.keys)
+                    .collect()
+            }
+
+            assert_eq!(keys::<offset::Offset>(), vec!["-1", "amount", "input"]);
+            assert_eq!(keys::<deploy::Deploy>(), vec!["--manifest", "--port", "-t", "--tag", "--target"]);
+
+            // Every config uses the crate's types.
+            let theme: deploy::Optional<String> = legacy::Legacy::parse_custom(args(&["legacy.exe"])).unwrap().theme;
+            assert_eq!(theme, Optional::None);
+        }
+
+        #[test]
+        fn config_trait() {
+            fn run<C: ArgumentConfig>(args: Vec<String>) -> Result<C, String> {
+                C::parse_custom(args.clone()).map_err(|error| C::render_error(error.as_ref(), &args))
+            }
+
+            assert_eq!(offset::Offset::EXECUTABLE_NAME, "offset.exe");
+            assert_eq!(run::<offset::Offset>(args(&["offset.exe", "5"])).unwrap().amount, 5);
+            assert!(run::<offset::Offset>(args(&["offset.exe"])).unwrap_err().ends_with("Usage: offset.exe <amount> [input] [-1]\n"));
+        }
         };
-        config::Config::show_help(Some(options)).unwrap();
     }
 
+    test_suite!();
+
     #[test]
-    fn parsing() {
-        let mut args = vec![
-            "appname.exec",
-            "/dev/null/input_file", // This is a required positional argument.
-            // "/dev/null/output_file", // This is an optional positional argument.
-            "-V", // This is a flag.
-            "-h", // This is a flag.
-            // "--template", "template_string" // This is an optional argument.
-        ];
-        let args: Vec<String> = args.iter_mut().map(|arg| arg.to_string()).collect();
-
-        let config = config::Config::parse_custom(args).unwrap();
-        println!("{:#?}", config);
-
-        assert_eq!(config.input_file, PathBuf::from("/dev/null/input_file"));
-        assert_eq!(config.output_file.to_option(), None);
-        assert_eq!(config.verbose, true);
-        assert_eq!(config.help, true);
-        assert_eq!(config.template.to_option(), Some("default_template_string".to_string()));
+    fn many_fields() {
+        let wide = wide::Wide::parse_custom(vec!["wide.exe".to_string(), "--f139".to_string()]).unwrap();
+        assert!(!wide.f000 && wide.f139);
+        assert_eq!(wide::Wide::get_arguments().unwrap().len(), 140);
+    }
+
+    // The same configs declared with `#[derive(Args)]`, run through the same tests.
+    mod derived {
+        use std::path::PathBuf;
+
+        use argument_handler_derive::Args;
+
+        use super::*;
+
+        // What `config_setup!` re-exports from its module.
+        pub mod shared {
+            #[allow(unused_imports)]
+            pub use crate::{
+                Argument, ArgumentOrder, CLIValue, DuplicatePolicy, Example, HelpDetail, HelpOptions, HelpText,
+                Optional, ParseError, ParseOptions, ParseStyle, ParseWarning, SplitAt, ValueError
+            };
+        }
+
+        pub mod config {
+            use super::*;
+            pub use super::shared::*;
+
+            #[derive(Debug, Args)]
+            #[args(
+                executable = "application.exe",
+                about = "Renders the input file with a template.",
+                long_about = "The rendered output is written next to the input file unless an output path is given.",
+                example("notes.txt", "Renders notes.txt to out.txt in the same directory."),
+                example("notes.txt -t=letter -V", "Uses the `letter` template and logs each step."),
+                after_help = "Report bugs at https://github.com/Zyoh/Rust-Argument-Handler.",
+            )]
+            pub struct Config {
+                #[arg(keys = ["input_file"], position = 0)]
+                pub input_file: PathBuf,
+                /// Saves to this file. Defaults to `out.txt` in the input file's parent directory.
+                #[arg(keys = ["output"], position = 1)]
+                pub output_file: Optional<PathBuf>,
+                /// Enables verbose logging.
+                #[arg(keys = ["-V", "--verbose"])]
+                pub verbose: bool,
+                /// Shows this help message.
+                #[arg(keys = ["-h", "--help"])]
+                pub help: bool,
+                /// The template to use.
+                ///
+                /// Templates are looked up in the current directory first.
+                ///
+                /// Set `TEMPLATE_DIR` to look somewhere else.
+                #[arg(keys = ["-t", "--template"], default = "default_template_string", section = "Advanced", hide_short_help)]
+                pub template: Optional<String>,
+
+                #[arg(internal = false)]
+                is_valid: bool,
+                #[arg(internal = PathBuf::from(""))]
+                path_loaded_from: PathBuf,
+            }
+        }
+
+        pub mod legacy {
+            use super::*;
+            pub use super::shared::*;
+
+            #[derive(Debug, Args)]
+            #[args(executable = "legacy.exe")]
+            pub struct Legacy {
+                /// Enables verbose logging.
+                #[arg(keys = ["-V", "--verbose"])]
+                pub verbose: bool,
+                /// Prints the version.
+                #[arg(keys = ["--version"])]
+                pub version: bool,
+                /// When to use colors.
+                #[arg(keys = ["--color"], default = "never", missing_value = "always", none_value = "none")]
+                pub color: Optional<String>,
+                /// The theme to use.
+                #[arg(keys = ["--theme"], aliases = ["--style"], hidden_aliases = ["--template"])]
+                pub theme: Optional<String>,
+                /// Disables logging.
+                #[arg(keys = ["-q", "--quiet"], hidden, deprecated = "logging is off unless --verbose is given")]
+                pub quiet: bool,
+                /// Logs every step.
+                #[cfg(any())]
+                #[arg(keys = ["--trace"])]
+                pub trace: bool,
+                /// Listens on a socket.
+                #[cfg(any())]
+                #[arg(keys = ["--socket"])]
+                pub socket: Socket,
+                /// The shell to run.
+                #[cfg(unix)]
+                #[arg(keys = ["--shell"], default = "/bin/sh")]
+                pub shell: PathBuf,
+                /// The shell to run.
+                #[cfg(not(unix))]
+                #[arg(keys = ["--shell"], default = "cmd.exe")]
+                pub shell: String,
+            }
+        }
+
+        pub mod dos {
+            use super::*;
+            pub use super::shared::*;
+
+            #[derive(Debug, Args)]
+            #[args(executable = "copy.exe")]
+            pub struct Copy {
+                #[arg(keys = ["source"], position = 0)]
+                pub source: PathBuf,
+                /// Enables verbose logging.
+                #[arg(keys = ["/Verbose", "+v"])]
+                pub verbose: bool,
+                /// Text or Binary.
+                #[arg(keys = ["/Mode"], default = "Text")]
+                pub mode: Mode,
+            }
+        }
+
+        pub mod offset {
+            use super::*;
+            pub use super::shared::*;
+
+            #[derive(Debug, Args)]
+            #[args(executable = "offset.exe")]
+            pub struct Offset {
+                /// How far to move.
+                #[arg(keys = ["amount"], position = 0)]
+                pub amount: i32,
+                /// Where to read from.
+                #[arg(keys = ["input"], position = 1, default = "stdin", allow_hyphen_values)]
+                pub input: String,
+                /// Moves by one line.
+                #[arg(keys = ["-1"])]
+                pub one: bool,
+            }
+        }
+
+        pub mod deploy {
+            use super::*;
+            pub use super::shared::*;
+
+            #[derive(Debug, Args)]
+            #[args(executable = "deploy.exe", duplicates = DuplicatePolicy::KeepFirst)]
+            pub struct Deploy {
+                /// Tag to deploy.
+                #[arg(keys = ["-t", "--tag"])]
+                pub tag: Option<String>,
+                /// Where to deploy.
+                #[arg(keys = ["--target"], duplicates = DuplicatePolicy::Error)]
+                pub target: Target,
+                /// Port of the deployed service.
+                #[arg(keys = ["--port"], default = "8080")]
+                pub port: Option<u16>,
+                /// Manifest to deploy from.
+                #[arg(keys = ["--manifest"])]
+                #[allow(dead_code)]
+                pub(crate) manifest: std::option::Option<PathBuf>,
+            }
+        }
+
+        pub mod build {
+            use super::*;
+
+            #[derive(Debug, Args)]
+            #[args(executable = "build.exe")]
+            pub struct Build {
+                /// Parallel jobs.
+                #[arg(keys = ["-j", "--jobs"], default = 4)]
+                pub jobs: u8,
+                /// Where to write the log.
+                #[arg(keys = ["--log"], default = PathBuf::from("build.log"))]
+                pub log: PathBuf,
+                /// Output directory.
+                #[arg(keys = ["--out"], default = PathBuf::from("target").join("out"), default_display = "target/out")]
+                pub out: PathBuf,
+                /// Build profile.
+                #[arg(keys = ["--profile"], default = Some("dev".to_string()), default_display = "dev")]
+                pub profile: Option<String>,
+                /// Where to write the summary.
+                #[arg(keys = ["--summary"], derived(report) = report.with_extension("md"))]
+                pub summary: PathBuf,
+                /// Where to write the report.
+                #[arg(keys = ["--report"], derived(out, profile) = out.join(format!("{}.txt", profile.as_deref().unwrap_or("dev"))))]
+                pub report: PathBuf,
+            }
+        }
+
+        pub mod layout {
+            use super::*;
+
+            #[derive(Debug, Args)]
+            #[args(executable = "layout.exe")]
+            pub struct Layout {
+                /// File to read.
+                #[arg(keys = ["input"], position = 0, section = "Input")]
+                pub input: PathBuf,
+                /// Encoding of the input.
+                #[arg(keys = ["--encoding"], default = "utf-8", section = "Input")]
+                pub encoding: String,
+                /// File to write.
+                #[arg(keys = ["-o", "--output"], default = "out.txt", section = "Output")]
+                pub output: PathBuf,
+                /// Rejects malformed input.
+                #[arg(keys = ["--strict"], section = "Input")]
+                pub strict: bool,
+                /// Enables verbose logging.
+                #[arg(keys = ["-V", "--verbose"])]
+                pub verbose: bool,
+            }
+        }
+
+        pub mod broken {
+            use super::*;
+
+            #[derive(Debug, Args)]
+            #[args(executable = "broken.exe")]
+            pub struct Broken {
+                /// How often to retry.
+                #[arg(keys = ["-r"], default = "many")]
+                pub retries: u8,
+            }
+        }
+
+        pub mod greet {
+            use super::*;
+
+            #[derive(Debug, Args)]
+            #[args(executable = "greet.exe")]
+            pub struct Greet {
+                /// Who to greet.
+                #[arg(keys = ["-n", "--name"])]
+                pub name: String,
+                /// Greets in capitals.
+                #[arg(keys = ["--loud"])]
+                pub loud: bool,
+            }
+        }
+
+        pub mod clash {
+            use super::*;
+
+            // No `Debug` here, and the derive leaves `Display` to the struct itself.
+            #[derive(Args)]
+            #[args(executable = "clash.exe")]
+            pub struct Clash {
+                /// Enables verbose logging.
+                #[arg(keys = ["-v", "--verbose"])]
+                pub verbose: bool,
+                /// Prints the version.
+                #[arg(keys = ["--version"], hidden_aliases = ["-v"])]
+                pub version: bool,
+            }
+
+            impl std::fmt::Display for Clash {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "verbose: {}, version: {}", self.verbose, self.version)
+                }
+            }
+        }
+
+        test_suite!();
     }
 }
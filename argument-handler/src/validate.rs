@@ -0,0 +1,116 @@
+// Copyright (c) 2023 Zoe <zoe@zyoh.ca>
+
+// Checks on the argument declaration, evaluated at compile time by `config_impl!`.
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+#[doc(hidden)]
+pub const fn starts_with_dash(key: &str) -> bool {
+    !key.is_empty() && key.as_bytes()[0] == b'-'
+}
+
+// Whether one of `keys` is found more than once among the keys of all arguments, which come per argument.
+#[doc(hidden)]
+pub const fn has_duplicate_key(all_keys: &[&[&str]], keys: &[&str]) -> bool {
+    let mut i = 0;
+    while i < keys.len() {
+        let mut count = 0;
+        let mut j = 0;
+        while j < all_keys.len() {
+            let mut k = 0;
+            while k < all_keys[j].len() {
+                if str_eq(keys[i], all_keys[j][k]) {
+                    count += 1;
+                }
+                k += 1;
+            }
+            j += 1;
+        }
+        if count > 1 {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+// Positions come per argument too, empty for options.
+#[doc(hidden)]
+pub const fn count_position(all_positions: &[&[usize]], position: usize) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i < all_positions.len() {
+        if !all_positions[i].is_empty() && all_positions[i][0] == position {
+            count += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+#[doc(hidden)]
+pub const fn count_positionals(all_positions: &[&[usize]]) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i < all_positions.len() {
+        count += all_positions[i].len();
+        i += 1;
+    }
+    count
+}
+
+// How often `name` has a derived default.
+#[doc(hidden)]
+pub const fn count_derived(derived: &[(&str, &[&str])], name: &str) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i < derived.len() {
+        if str_eq(derived[i].0, name) {
+            count += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+// Whether the derived default of `name` reads `target`, directly or through other derived defaults.
+// A path longer than the number of derived defaults repeats one, so the search stops there.
+const fn derived_reads(derived: &[(&str, &[&str])], name: &str, target: &str, depth: usize) -> bool {
+    if depth == 0 {
+        return false;
+    }
+    let mut i = 0;
+    while i < derived.len() {
+        if str_eq(derived[i].0, name) {
+            let dependencies = derived[i].1;
+            let mut j = 0;
+            while j < dependencies.len() {
+                if str_eq(dependencies[j], target) || derived_reads(derived, dependencies[j], target, depth - 1) {
+                    return true;
+                }
+                j += 1;
+            }
+        }
+        i += 1;
+    }
+    false
+}
+
+// Whether the derived default of `name` ends up reading itself.
+#[doc(hidden)]
+pub const fn derived_cycle(derived: &[(&str, &[&str])], name: &str) -> bool {
+    derived_reads(derived, name, name, derived.len())
+}